@@ -0,0 +1,135 @@
+//! This example demonstrates drawing text using an anti-aliased, proportional font.
+//!
+//! Applications usually generate the glyphs from a font file ahead of time. To keep this example
+//! self-contained, the glyphs are created by downsampling a larger monospace font and trimming
+//! the empty columns around each glyph.
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::{BinaryColor, Rgb888},
+    prelude::*,
+    text::{Baseline, Text},
+};
+use embedded_graphics_simulator::{OutputSettingsBuilder, SimulatorDisplay, Window};
+use embedded_text::{
+    prelude::*,
+    renderer::{GrayscaleFont, GrayscaleGlyph, GrayscaleTextStyle},
+    style::color::Rgb,
+};
+
+/// A draw target that records the pixels of a single glyph of `FONT_10X20`.
+struct GlyphBitmap([[bool; 10]; 20]);
+
+impl OriginDimensions for GlyphBitmap {
+    fn size(&self) -> Size {
+        Size::new(10, 20)
+    }
+}
+
+impl DrawTarget for GlyphBitmap {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(row) = self.0.get_mut(point.y as usize) {
+                if let Some(pixel) = row.get_mut(point.x as usize) {
+                    *pixel = color.is_on();
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates a glyph of half the size of the `FONT_10X20` glyph of `c`.
+fn downsampled_glyph(c: char) -> GrayscaleGlyph<'static> {
+    let mut bitmap = GlyphBitmap([[false; 10]; 20]);
+    let style = MonoTextStyle::new(&FONT_10X20, BinaryColor::On);
+    let mut buffer = [0; 4];
+    Text::with_baseline(
+        c.encode_utf8(&mut buffer),
+        Point::zero(),
+        style,
+        Baseline::Top,
+    )
+    .draw(&mut bitmap)
+    .unwrap();
+
+    // Every pixel of the new glyph covers 2x2 pixels of the original one.
+    let mut coverage = [[0u8; 5]; 10];
+    for (y, row) in bitmap.0.iter().enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            if *pixel {
+                coverage[y / 2][x / 2] += 255 / 4;
+            }
+        }
+    }
+
+    let is_empty = |column: usize| coverage.iter().all(|row| row[column] == 0);
+    let first = (0..5).find(|column| !is_empty(*column)).unwrap_or(0);
+    let last = (0..5).rev().find(|column| !is_empty(*column)).unwrap_or(0);
+    let width = last - first + 1;
+
+    let pixels = coverage
+        .iter()
+        .flat_map(|row| row[first..=last].iter().copied())
+        .collect::<Vec<_>>();
+
+    GrayscaleGlyph::new(
+        c,
+        Box::leak(pixels.into_boxed_slice()),
+        width as u32,
+        Point::new(0, -8),
+        width as u32 + 1,
+    )
+}
+
+fn main() {
+    let mut glyphs = vec![GrayscaleGlyph::new(' ', &[], 0, Point::zero(), 3)];
+    glyphs.extend(('!'..='~').map(downsampled_glyph));
+
+    let font = GrayscaleFont {
+        glyphs: Box::leak(glyphs.into_boxed_slice()),
+        ascent: 8,
+        descent: 2,
+        replacement_character: '?',
+    };
+
+    let text = "Anti-aliased glyphs are blended with the background color, \
+    which can be changed by \x1b[48;2;32;64;128mANSI sequences\x1b[48;2;16;16;16m, too.";
+
+    // Specify the styling options:
+    // * Use the downsampled font, with light gray text.
+    // * Blend the edges of the glyphs with the background color of the text box.
+    let mut character_style = GrayscaleTextStyle::new(&font, Rgb888::new(224, 224, 224));
+    character_style.background_color = Some(Rgb888::new(16, 16, 16));
+
+    let textbox_style = TextBoxStyleBuilder::new()
+        .height_mode(FitToText)
+        .alignment(Justified)
+        .padding(Padding::all(4))
+        .background_color(Rgb::new(16, 16, 16))
+        .build();
+
+    // Specify the bounding box. Note the 0px height. The `FitToText` height mode will
+    // measure and adjust the height of the text box.
+    let bounds = Rectangle::new(Point::zero(), Size::new(128, 0));
+
+    // Create the text box and apply styling options.
+    let text_box = TextBox::with_textbox_style(text, bounds, character_style, textbox_style);
+
+    // Create a simulated display with the dimensions of the text box.
+    let mut display = SimulatorDisplay::new(text_box.bounding_box().size);
+
+    // Draw the text box.
+    text_box.draw(&mut display).unwrap();
+
+    // Set up the window and show the display's contents.
+    let output_settings = OutputSettingsBuilder::new().scale(3).build();
+    Window::new("Anti-aliased text example", &output_settings).show_static(&display);
+}
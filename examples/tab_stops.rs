@@ -0,0 +1,56 @@
+//! This example demonstrates aligning columns using explicit tab stops.
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use embedded_graphics_simulator::{
+    BinaryColorTheme, OutputSettingsBuilder, SimulatorDisplay, Window,
+};
+use embedded_text::{prelude::*, style::TabStop};
+
+// Item names are left aligned, quantities are centered and prices are aligned on the decimal point.
+static TAB_STOPS: [TabStop; 2] = [TabStop::center(108), TabStop::decimal(156)];
+
+fn main() {
+    let text = "Item\tQty\tPrice\n\
+        Coffee\t2\t3.50\n\
+        Croissant\t1\t12.25\n\
+        Water\t10\t0.9";
+
+    // Specify the styling options:
+    // * Use the 6x9 MonoFont from embedded-graphics.
+    // * Use `FitToText` height mode to stretch the text box to the exact height of the text.
+    // * Draw the text with `BinaryColor::On`, which will be displayed as light blue.
+    // * Align columns using explicit tab stops.
+    let character_style = MonoTextStyleBuilder::new()
+        .font(&FONT_6X9)
+        .text_color(BinaryColor::On)
+        .build();
+
+    let textbox_style = TextBoxStyleBuilder::new()
+        .height_mode(FitToText)
+        .tab_stops(&TAB_STOPS)
+        .build();
+
+    // Specify the bounding box. Note the 0px height. The `FitToText` height mode will
+    // measure and adjust the height of the text box.
+    let bounds = Rectangle::new(Point::zero(), Size::new(180, 0));
+
+    // Create the text box and apply styling options.
+    let text_box = TextBox::with_textbox_style(text, bounds, character_style, textbox_style);
+
+    // Create a simulated display with the dimensions of the text box.
+    let mut display = SimulatorDisplay::new(text_box.bounding_box().size);
+
+    // Draw the text box.
+    text_box.draw(&mut display).unwrap();
+
+    // Set up the window and show the display's contents.
+    let output_settings = OutputSettingsBuilder::new()
+        .theme(BinaryColorTheme::OledBlue)
+        .build();
+    Window::new("Tab stops example", &output_settings).show_static(&display);
+}
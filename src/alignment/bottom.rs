@@ -1,5 +1,5 @@
 //! Bottom vertical text alignment.
-use embedded_graphics::{geometry::Dimensions, text::renderer::TextRenderer};
+use embedded_graphics::text::renderer::TextRenderer;
 
 use crate::{
     alignment::{HorizontalTextAlignment, VerticalTextAlignment},
@@ -26,12 +26,16 @@ impl VerticalTextAlignment for BottomAligned {
             &styled_text_box.character_style,
             styled_text_box.text,
             cursor.line_width(),
-        ) as i32;
+        );
+
+        let box_height = styled_text_box.text_area().size.height;
 
-        let box_height = styled_text_box.bounding_box().size.height as i32;
-        let offset = box_height - text_height;
+        cursor.y += Self::vertical_offset(text_height, box_height);
+    }
 
-        cursor.y += offset
+    #[inline]
+    fn vertical_offset(text_height: u32, box_height: u32) -> i32 {
+        box_height as i32 - text_height as i32
     }
 }
 
@@ -6,7 +6,7 @@ use crate::{
     TextBox,
 };
 
-use embedded_graphics::{geometry::Dimensions, text::renderer::TextRenderer};
+use embedded_graphics::text::renderer::TextRenderer;
 
 /// Marks text to be rendered center aligned.
 ///
@@ -43,12 +43,16 @@ impl VerticalTextAlignment for CenterAligned {
             &styled_text_box.character_style,
             styled_text_box.text,
             cursor.line_width(),
-        ) as i32;
+        );
+
+        let box_height = styled_text_box.text_area().size.height;
 
-        let box_height = styled_text_box.bounding_box().size.height as i32;
-        let offset = (box_height - text_height) / 2;
+        cursor.y += Self::vertical_offset(text_height, box_height);
+    }
 
-        cursor.y += offset;
+    #[inline]
+    fn vertical_offset(text_height: u32, box_height: u32) -> i32 {
+        (box_height as i32 - text_height as i32) / 2
     }
 }
 
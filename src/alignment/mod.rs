@@ -50,6 +50,19 @@ pub trait VerticalTextAlignment: Copy {
         S: TextRenderer,
         A: HorizontalTextAlignment,
         H: HeightMode;
+
+    /// Returns the vertical offset of the text, given the height of the text and the height of
+    /// the area available for the text.
+    ///
+    /// The offset must match the distance `apply_vertical_alignment` moves the cursor by. The
+    /// default implementation returns 0, which is correct for alignments that leave the cursor
+    /// at the top of the text box.
+    #[inline]
+    fn vertical_offset(text_height: u32, box_height: u32) -> i32 {
+        let _ = (text_height, box_height);
+
+        0
+    }
 }
 
 pub use bottom::BottomAligned;
@@ -5,7 +5,7 @@ use crate::{
     style::height_mode::HeightMode,
     TextBox,
 };
-use embedded_graphics::{geometry::Dimensions, text::renderer::TextRenderer};
+use embedded_graphics::text::renderer::TextRenderer;
 
 /// Align text to the TextBox so that the last lines are always displayed.
 ///
@@ -29,13 +29,19 @@ impl VerticalTextAlignment for Scrolling {
             &styled_text_box.character_style,
             styled_text_box.text,
             cursor.line_width(),
-        ) as i32;
+        );
 
-        let box_height = styled_text_box.bounding_box().size.height as i32;
-        if text_height > box_height {
-            let offset = box_height - text_height;
+        let box_height = styled_text_box.text_area().size.height;
+
+        cursor.y += Self::vertical_offset(text_height, box_height);
+    }
 
-            cursor.y += offset
+    #[inline]
+    fn vertical_offset(text_height: u32, box_height: u32) -> i32 {
+        if text_height > box_height {
+            box_height as i32 - text_height as i32
+        } else {
+            0
         }
     }
 }
@@ -44,14 +50,15 @@ impl VerticalTextAlignment for Scrolling {
 mod test {
     use embedded_graphics::{
         mock_display::MockDisplay,
-        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        mono_font::{ascii::FONT_6X9, MonoTextStyle, MonoTextStyleBuilder},
         pixelcolor::BinaryColor,
         prelude::*,
         primitives::Rectangle,
     };
 
     use crate::{
-        alignment::Scrolling,
+        alignment::{LeftAligned, Scrolling},
+        animation::{Animation, ScrollTransition},
         style::{height_mode::Exact, vertical_overdraw::Hidden, TextBoxStyleBuilder},
         utils::test::size_for,
         TextBox,
@@ -201,4 +208,76 @@ mod test {
             "..............................",
         ]);
     }
+
+    fn smooth_scrolling_text_box(
+        text: &str,
+    ) -> TextBox<'_, MonoTextStyle<'static, BinaryColor>, LeftAligned, Scrolling, Exact<Hidden>>
+    {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .vertical_alignment(Scrolling)
+            .height_mode(Exact(Hidden))
+            .build();
+
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2)),
+            character_style,
+            style,
+        )
+    }
+
+    #[test]
+    fn scroll_transition_starts_at_the_previous_position() {
+        let mut text_box = smooth_scrolling_text_box("word word2 word3 word4");
+
+        let transition = text_box.scroll_transition("word word2 word3", 9);
+        assert_eq!(transition, ScrollTransition::new(9, 0, 9));
+
+        text_box.animation = Animation::new().scroll_offset(transition.start);
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        let mut expected = MockDisplay::new();
+        smooth_scrolling_text_box("word word2 word3")
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn scroll_offset_moves_lines_by_pixels() {
+        let mut text_box = smooth_scrolling_text_box("word word2 word3 word4");
+        text_box.animation = Animation::new().scroll_offset(4);
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "#.#.#..#..#..#.....#..#...#...",
+            ".#.#....##...#......###..####.",
+            "..............................",
+            "..............................",
+            "..............................",
+            "......................#..####.",
+            "......................#....#..",
+            "#...#...##...#.#....###...##..",
+            "#.#.#..#..#..##.#..#..#.....#.",
+            "#.#.#..#..#..#.....#..#.....#.",
+            ".#.#....##...#......###..###..",
+            "..............................",
+            "..............................",
+            "..............................",
+            "......................#....#..",
+            "......................#...##..",
+            "#...#...##...#.#....###..#.#..",
+            "#.#.#..#..#..##.#..#..#.#..#..",
+        ]);
+    }
 }
@@ -0,0 +1,380 @@
+//! Animation state of a text box.
+//!
+//! Animated text boxes are drawn repeatedly, with the [`Animation`] of the text box describing the
+//! current frame. The text is laid out the same way in every frame.
+//!
+//! [`Animation`]: struct.Animation.html
+
+/// The current frame of an animated [`TextBox`].
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{animation::Animation, prelude::*};
+///
+/// let mut text_box = TextBox::new(
+///     "\x1b[5mWARNING\x1b[25m Pressure high",
+///     Rectangle::new(Point::zero(), Size::new(60, 18)),
+///     MonoTextStyle::new(&FONT_6X9, BinaryColor::On),
+/// );
+///
+/// for tick in 0..4 {
+///     text_box.animation = Animation::new().tick(tick);
+///
+///     let mut display = MockDisplay::new();
+///     text_box.draw(&mut display).unwrap();
+/// }
+/// ```
+///
+/// [`TextBox`]: ../struct.TextBox.html
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Animation {
+    /// The current tick, usually a frame counter.
+    ///
+    /// Blinking text is switched on and off as the tick changes, as configured by the
+    /// `blink_interval` and `blink_mode` options of the text box style.
+    pub tick: u32,
+
+    /// The part of the text that is drawn, or `None` to draw the whole text.
+    ///
+    /// The text is laid out as if it was drawn completely, so the revealed text doesn't move as
+    /// it grows.
+    pub reveal: Option<Reveal>,
+
+    /// Vertical offset of the text in pixels, added to the offset of the vertical alignment.
+    ///
+    /// Positive values move the text down. The offset is usually animated using a
+    /// [`ScrollTransition`]. Vertical text ignores the offset.
+    ///
+    /// [`ScrollTransition`]: struct.ScrollTransition.html
+    pub scroll_offset: i32,
+}
+
+impl Animation {
+    /// Creates a new `Animation` object at the first tick.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            tick: 0,
+            reveal: None,
+            scroll_offset: 0,
+        }
+    }
+
+    /// Sets the current tick.
+    #[inline]
+    #[must_use]
+    pub const fn tick(self, tick: u32) -> Self {
+        Self { tick, ..self }
+    }
+
+    /// Only draws the beginning of the text.
+    #[inline]
+    #[must_use]
+    pub const fn reveal(self, reveal: Reveal) -> Self {
+        Self {
+            reveal: Some(reveal),
+            ..self
+        }
+    }
+
+    /// Sets the vertical offset of the text.
+    #[inline]
+    #[must_use]
+    pub const fn scroll_offset(self, scroll_offset: i32) -> Self {
+        Self {
+            scroll_offset,
+            ..self
+        }
+    }
+}
+
+/// The beginning of the text that is drawn by a typewriter animation.
+///
+/// Vertical text is always drawn completely.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{
+///     alignment::CenterAligned,
+///     animation::{Animation, Reveal},
+///     prelude::*,
+///     style::TextBoxStyle,
+/// };
+///
+/// let text = "Welcome! Press any key to continue.";
+/// let mut text_box = TextBox::with_textbox_style(
+///     text,
+///     Rectangle::new(Point::zero(), Size::new(64, 36)),
+///     MonoTextStyle::new(&FONT_6X9, BinaryColor::On),
+///     TextBoxStyle::with_alignment(CenterAligned),
+/// );
+///
+/// // Type one character per frame. Words stay in place as the text is revealed.
+/// for characters in 0..=text.len() as u32 {
+///     text_box.animation = Animation::new().reveal(Reveal::Characters(characters));
+///
+///     let mut display = MockDisplay::new();
+///     text_box.draw(&mut display).unwrap();
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Reveal {
+    /// The first given number of characters is drawn.
+    ///
+    /// Whitespace and escape sequences are not counted.
+    Characters(u32),
+
+    /// The given number of pixels is drawn, as the lines are read from left to right.
+    ///
+    /// Each line counts with the width of its text, excluding the space that is used for
+    /// alignment and indentation.
+    Pixels(u32),
+}
+
+/// A linear transition between two scroll offsets.
+///
+/// Text that is appended to a text box with [`Scrolling`] alignment moves the existing lines up at
+/// once. To scroll smoothly instead, create a transition using [`TextBox::scroll_transition`] and
+/// draw the frames with the interpolated [`Animation::scroll_offset`]. Using the [`Hidden`]
+/// vertical overdraw mode, the top line is scrolled out pixel by pixel.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{
+///     alignment::{LeftAligned, Scrolling},
+///     animation::Animation,
+///     prelude::*,
+///     style::{height_mode::Exact, vertical_overdraw::Hidden, TextBoxStyleBuilder},
+/// };
+///
+/// let previous_text = "$ ls\nsrc";
+/// let text = "$ ls\nsrc\ntarget";
+///
+/// let style = TextBoxStyleBuilder::new()
+///     .alignment(LeftAligned)
+///     .vertical_alignment(Scrolling)
+///     .height_mode(Exact(Hidden))
+///     .build();
+/// let mut text_box = TextBox::with_textbox_style(
+///     text,
+///     Rectangle::new(Point::zero(), Size::new(60, 18)),
+///     MonoTextStyle::new(&FONT_6X9, BinaryColor::On),
+///     style,
+/// );
+///
+/// // Scroll the new line in over 8 frames.
+/// let transition = text_box.scroll_transition(previous_text, 8);
+/// for frame in 0..=transition.duration {
+///     text_box.animation = Animation::new().scroll_offset(transition.offset_at(frame));
+///
+///     let mut display = MockDisplay::new();
+///     text_box.draw(&mut display).unwrap();
+/// }
+/// ```
+///
+/// [`Scrolling`]: ../alignment/struct.Scrolling.html
+/// [`Hidden`]: ../style/vertical_overdraw/struct.Hidden.html
+/// [`TextBox::scroll_transition`]: ../struct.TextBox.html#method.scroll_transition
+/// [`Animation::scroll_offset`]: struct.Animation.html#structfield.scroll_offset
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ScrollTransition {
+    /// The scroll offset at the start of the transition.
+    pub start: i32,
+
+    /// The scroll offset at the end of the transition.
+    pub end: i32,
+
+    /// The number of ticks the transition takes.
+    pub duration: u32,
+}
+
+impl ScrollTransition {
+    /// Creates a new `ScrollTransition`.
+    #[inline]
+    #[must_use]
+    pub const fn new(start: i32, end: i32, duration: u32) -> Self {
+        Self {
+            start,
+            end,
+            duration,
+        }
+    }
+
+    /// Returns the scroll offset after `elapsed` ticks.
+    #[inline]
+    #[must_use]
+    pub fn offset_at(&self, elapsed: u32) -> i32 {
+        if elapsed >= self.duration {
+            return self.end;
+        }
+
+        let distance = i64::from(self.end) - i64::from(self.start);
+        let offset = distance * i64::from(elapsed) / i64::from(self.duration);
+
+        self.start + offset as i32
+    }
+
+    /// Returns `true` if the transition is complete after `elapsed` ticks.
+    #[inline]
+    #[must_use]
+    pub fn is_finished(&self, elapsed: u32) -> bool {
+        elapsed >= self.duration
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::{CenterAligned, HorizontalTextAlignment, Justified},
+        animation::{Animation, Reveal, ScrollTransition},
+        style::TextBoxStyle,
+        utils::test::size_for,
+        TextBox,
+    };
+
+    fn draw_revealed<A: HorizontalTextAlignment>(
+        text: &str,
+        size: Size,
+        alignment: A,
+        reveal: Reveal,
+    ) -> MockDisplay<BinaryColor> {
+        let mut text_box = TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size),
+            MonoTextStyle::new(&FONT_6X9, BinaryColor::On),
+            TextBoxStyle::with_alignment(alignment),
+        );
+        text_box.animation = Animation::new().reveal(reveal);
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        display
+    }
+
+    #[test]
+    fn revealing_characters_keeps_the_layout() {
+        let display = draw_revealed(
+            "ab cd",
+            size_for(&FONT_6X9, 3, 2),
+            CenterAligned,
+            Reveal::Characters(3),
+        );
+
+        display.assert_pattern(&[
+            "                  ",
+            "          #       ",
+            "          #       ",
+            "     ###  ###     ",
+            "    #  #  #  #    ",
+            "    #  #  #  #    ",
+            "     ###  ###     ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "     ###          ",
+            "    #             ",
+            "    #             ",
+            "     ###          ",
+        ]);
+    }
+
+    #[test]
+    fn revealed_justified_text_is_not_moved() {
+        let display = draw_revealed(
+            "a b c",
+            size_for(&FONT_6X9, 5, 1),
+            Justified,
+            Reveal::Characters(2),
+        );
+
+        display.assert_pattern(&[
+            "                              ",
+            "             #                ",
+            "             #                ",
+            "  ###        ###              ",
+            " #  #        #  #             ",
+            " #  #        #  #             ",
+            "  ###        ###              ",
+        ]);
+    }
+
+    #[test]
+    fn revealing_pixels() {
+        let display = draw_revealed(
+            "ab cd",
+            size_for(&FONT_6X9, 2, 2),
+            CenterAligned,
+            Reveal::Pixels(15),
+        );
+
+        display.assert_pattern(&[
+            "            ",
+            "       #    ",
+            "       #    ",
+            "  ###  ###  ",
+            " #  #  #  # ",
+            " #  #  #  # ",
+            "  ###  ###  ",
+            "            ",
+            "            ",
+            "            ",
+            "            ",
+            "            ",
+            "  #         ",
+            " #          ",
+            " #          ",
+            "  #         ",
+        ]);
+    }
+
+    #[test]
+    fn scroll_transition_interpolates_linearly() {
+        let transition = ScrollTransition::new(9, 0, 4);
+
+        assert_eq!(transition.offset_at(0), 9);
+        assert_eq!(transition.offset_at(1), 7);
+        assert_eq!(transition.offset_at(2), 5);
+        assert_eq!(transition.offset_at(3), 3);
+        assert_eq!(transition.offset_at(4), 0);
+        assert_eq!(transition.offset_at(10), 0);
+        assert!(!transition.is_finished(3));
+        assert!(transition.is_finished(4));
+
+        // Empty transitions are finished immediately.
+        assert_eq!(ScrollTransition::new(9, 0, 0).offset_at(0), 0);
+    }
+}
@@ -0,0 +1,895 @@
+//! Syntax highlighting.
+//!
+//! This module splits code snippets into tokens and draws them using the styles of a [`Theme`].
+//! It is enabled by the `highlight` feature.
+//!
+//! [`Highlighted`] spans tokenize the text while it is drawn, so highlighting doesn't need a
+//! buffer to store the tokens. Indentation is usually done using tabs or spaces,
+//! so monospace fonts and a fixed [`TabSize`] give the best results.
+//!
+//! # Example
+//!
+//! ```rust
+//! use embedded_graphics::{
+//!     mock_display::MockDisplay,
+//!     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+//!     pixelcolor::Rgb888,
+//!     prelude::*,
+//! };
+//! use embedded_text::{
+//!     highlight::{Highlighted, Language, Theme},
+//!     prelude::*,
+//!     renderer::SpanStyle,
+//!     style::TabSize,
+//! };
+//!
+//! let text = "{\n\t\"ssid\": \"home\",\n\t\"channel\": 6\n}";
+//!
+//! let theme = Theme {
+//!     key: SpanStyle::new().text_color(Rgb888::CYAN),
+//!     string: SpanStyle::new().text_color(Rgb888::GREEN),
+//!     number: SpanStyle::new().text_color(Rgb888::YELLOW),
+//!     ..Theme::default()
+//! };
+//!
+//! let highlighted = Highlighted::new(text, Language::Json, &theme);
+//! let textbox_style = TextBoxStyleBuilder::new()
+//!     .tab_size(TabSize::Spaces(2))
+//!     .build();
+//!
+//! let text_box = TextBox::with_spans(
+//!     text,
+//!     Rectangle::new(Point::zero(), Size::new(120, 36)),
+//!     MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+//!     textbox_style,
+//!     &highlighted,
+//! );
+//!
+//! let mut display: MockDisplay<Rgb888> = MockDisplay::new();
+//! text_box.draw(&mut display).unwrap();
+//! ```
+//!
+//! [`Theme`]: struct.Theme.html
+//! [`Highlighted`]: struct.Highlighted.html
+//! [`TabSize`]: ../style/enum.TabSize.html
+use core::{cell::Cell, fmt, ops::Range};
+
+use crate::renderer::{SpanStyle, Spans};
+use embedded_graphics::text::renderer::CharacterStyle;
+
+/// The kind of a highlighted token.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Highlight {
+    /// A keyword or a literal like `true` and `null`.
+    Keyword,
+
+    /// A string.
+    String,
+
+    /// A number.
+    Number,
+
+    /// A comment.
+    Comment,
+
+    /// A key of an object or a configuration file.
+    Key,
+
+    /// A section header of a configuration file.
+    Section,
+
+    /// Punctuation, like brackets and separators.
+    Punctuation,
+}
+
+/// The language of the highlighted text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Language<'a> {
+    /// JSON.
+    Json,
+
+    /// INI configuration files.
+    Ini,
+
+    /// A language with C-like syntax and the given keywords.
+    ///
+    /// Use [`C_KEYWORDS`] for C code.
+    ///
+    /// [`C_KEYWORDS`]: constant.C_KEYWORDS.html
+    CLike(&'a [&'a str]),
+}
+
+/// The keywords of the C language.
+pub const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "int", "long", "register", "return", "short",
+    "signed", "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+    "volatile", "while",
+];
+
+/// The state of the INI tokenizer in the current line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum IniState {
+    /// Nothing but whitespace has been found in the line.
+    LineStart,
+
+    /// After a key, before the separator.
+    Key,
+
+    /// After the separator of a key.
+    Value,
+
+    /// The rest of the line is not highlighted.
+    Other,
+}
+
+/// Returns the length of the string literal at the start of `text`.
+///
+/// Strings end at the closing quote, or at the end of the line if they are not terminated.
+fn string_len(text: &str, quote: char) -> usize {
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return index,
+            c if c == quote => return index + 1,
+            _ => {}
+        }
+    }
+
+    text.len()
+}
+
+/// Returns the length of the longest prefix of `text` that only contains matching characters.
+fn prefix_len(text: &str, matches: impl Fn(char) -> bool) -> usize {
+    text.find(|c| !matches(c)).unwrap_or(text.len())
+}
+
+fn is_number_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '.'
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Produces the highlighted tokens of a text, one at a time.
+trait TokenSource: Copy {
+    /// Returns the byte range and the kind of the next highlighted token.
+    fn next_token(&mut self) -> Option<(usize, usize, Highlight)>;
+}
+
+/// Splits text into tokens.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct Lexer<'a> {
+    text: &'a str,
+    language: Language<'a>,
+    pos: usize,
+    ini_state: IniState,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str, language: Language<'a>) -> Self {
+        Self {
+            text,
+            language,
+            pos: 0,
+            ini_state: IniState::LineStart,
+        }
+    }
+
+    fn json_token(rest: &str, c: char) -> (usize, Option<Highlight>) {
+        match c {
+            '"' => {
+                let len = string_len(rest, '"');
+                let highlight = if rest[len..].trim_start().starts_with(':') {
+                    Highlight::Key
+                } else {
+                    Highlight::String
+                };
+
+                (len, Some(highlight))
+            }
+
+            '-' | '0'..='9' => (
+                prefix_len(rest, |c| is_number_char(c) || c == '-' || c == '+'),
+                Some(Highlight::Number),
+            ),
+
+            '{' | '}' | '[' | ']' | ':' | ',' => (1, Some(Highlight::Punctuation)),
+
+            c if c.is_alphabetic() => {
+                let len = prefix_len(rest, char::is_alphanumeric);
+                let highlight = match &rest[..len] {
+                    "true" | "false" | "null" => Some(Highlight::Keyword),
+                    _ => None,
+                };
+
+                (len, highlight)
+            }
+
+            _ => (c.len_utf8(), None),
+        }
+    }
+
+    fn ini_token(&mut self, rest: &str, c: char) -> (usize, Option<Highlight>) {
+        if c == '\n' {
+            self.ini_state = IniState::LineStart;
+            return (1, None);
+        }
+        if c.is_whitespace() {
+            return (c.len_utf8(), None);
+        }
+
+        let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        match self.ini_state {
+            IniState::LineStart => match c {
+                ';' | '#' => (line.len(), Some(Highlight::Comment)),
+
+                '[' => {
+                    self.ini_state = IniState::Other;
+                    let len = line.find(']').map_or(line.len(), |index| index + 1);
+
+                    (len, Some(Highlight::Section))
+                }
+
+                _ => {
+                    self.ini_state = IniState::Key;
+                    let key = &line[..line.find(&['=', ':'][..]).unwrap_or(line.len())];
+
+                    (key.trim_end().len(), Some(Highlight::Key))
+                }
+            },
+
+            IniState::Key => {
+                if c == '=' || c == ':' {
+                    self.ini_state = IniState::Value;
+                    (1, Some(Highlight::Punctuation))
+                } else {
+                    self.ini_state = IniState::Other;
+                    (c.len_utf8(), None)
+                }
+            }
+
+            IniState::Value => {
+                self.ini_state = IniState::Other;
+                let value = line.trim_end();
+                let highlight = match value {
+                    "true" | "false" | "yes" | "no" | "on" | "off" => Highlight::Keyword,
+                    _ if value.chars().all(|c| is_number_char(c) || c == '-') => Highlight::Number,
+                    _ => Highlight::String,
+                };
+
+                (value.len(), Some(highlight))
+            }
+
+            IniState::Other => (c.len_utf8(), None),
+        }
+    }
+
+    fn c_token(rest: &str, c: char, keywords: &[&str]) -> (usize, Option<Highlight>) {
+        if rest.starts_with("//") {
+            return (prefix_len(rest, |c| c != '\n'), Some(Highlight::Comment));
+        }
+        if rest.starts_with("/*") {
+            let len = rest
+                .match_indices("*/")
+                .find(|(index, _)| *index >= 2)
+                .map_or(rest.len(), |(index, _)| index + 2);
+            return (len, Some(Highlight::Comment));
+        }
+
+        match c {
+            '"' | '\'' => (string_len(rest, c), Some(Highlight::String)),
+
+            '0'..='9' => (prefix_len(rest, is_number_char), Some(Highlight::Number)),
+
+            '{' | '}' | '(' | ')' | '[' | ']' | ';' | ',' | '.' | '+' | '-' | '*' | '/' | '%'
+            | '=' | '<' | '>' | '!' | '&' | '|' | '^' | '~' | '?' | ':' | '#' => {
+                (1, Some(Highlight::Punctuation))
+            }
+
+            c if is_identifier_char(c) => {
+                let len = prefix_len(rest, is_identifier_char);
+                let highlight = if keywords.contains(&&rest[..len]) {
+                    Some(Highlight::Keyword)
+                } else {
+                    None
+                };
+
+                (len, highlight)
+            }
+
+            _ => (c.len_utf8(), None),
+        }
+    }
+}
+
+impl TokenSource for Lexer<'_> {
+    fn next_token(&mut self) -> Option<(usize, usize, Highlight)> {
+        loop {
+            let rest = &self.text[self.pos..];
+            let c = rest.chars().next()?;
+
+            let (len, highlight) = match self.language {
+                Language::Json => Self::json_token(rest, c),
+                Language::Ini => self.ini_token(rest, c),
+                Language::CLike(keywords) => Self::c_token(rest, c, keywords),
+            };
+
+            let start = self.pos;
+            self.pos += len.max(c.len_utf8());
+
+            if let Some(highlight) = highlight {
+                return Some((start, self.pos, highlight));
+            }
+        }
+    }
+}
+
+/// An iterator over the highlighted tokens of a text.
+///
+/// Each item is the byte range of a token and its kind. Text between the tokens is not
+/// highlighted.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_text::highlight::{Highlight, Language, Tokens};
+///
+/// let mut tokens = Tokens::new("volume = 20", Language::Ini);
+///
+/// assert_eq!(tokens.next(), Some((0..6, Highlight::Key)));
+/// assert_eq!(tokens.next(), Some((7..8, Highlight::Punctuation)));
+/// assert_eq!(tokens.next(), Some((9..11, Highlight::Number)));
+/// assert_eq!(tokens.next(), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Tokens<'a> {
+    lexer: Lexer<'a>,
+}
+
+impl<'a> Tokens<'a> {
+    /// Creates an iterator over the tokens of `text`.
+    #[inline]
+    #[must_use]
+    pub fn new(text: &'a str, language: Language<'a>) -> Self {
+        Self {
+            lexer: Lexer::new(text, language),
+        }
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = (Range<usize>, Highlight);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lexer
+            .next_token()
+            .map(|(start, end, highlight)| (start..end, highlight))
+    }
+}
+
+/// The styles of the highlighted tokens.
+///
+/// Tokens are drawn using the character style of the text, changed by the span style of their
+/// kind.
+#[derive(Clone)]
+pub struct Theme<S>
+where
+    S: CharacterStyle,
+{
+    /// The style of keywords.
+    pub keyword: SpanStyle<S>,
+
+    /// The style of strings.
+    pub string: SpanStyle<S>,
+
+    /// The style of numbers.
+    pub number: SpanStyle<S>,
+
+    /// The style of comments.
+    pub comment: SpanStyle<S>,
+
+    /// The style of keys.
+    pub key: SpanStyle<S>,
+
+    /// The style of section headers.
+    pub section: SpanStyle<S>,
+
+    /// The style of punctuation.
+    pub punctuation: SpanStyle<S>,
+}
+
+impl<S> Theme<S>
+where
+    S: CharacterStyle,
+{
+    /// Returns the style of the given kind of tokens.
+    #[inline]
+    #[must_use]
+    pub fn style(&self, highlight: Highlight) -> &SpanStyle<S> {
+        match highlight {
+            Highlight::Keyword => &self.keyword,
+            Highlight::String => &self.string,
+            Highlight::Number => &self.number,
+            Highlight::Comment => &self.comment,
+            Highlight::Key => &self.key,
+            Highlight::Section => &self.section,
+            Highlight::Punctuation => &self.punctuation,
+        }
+    }
+}
+
+impl<S> Default for Theme<S>
+where
+    S: CharacterStyle,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            keyword: SpanStyle::new(),
+            string: SpanStyle::new(),
+            number: SpanStyle::new(),
+            comment: SpanStyle::new(),
+            key: SpanStyle::new(),
+            section: SpanStyle::new(),
+            punctuation: SpanStyle::new(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for Theme<S>
+where
+    S: CharacterStyle + fmt::Debug,
+    S::Color: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Theme")
+            .field("keyword", &self.keyword)
+            .field("string", &self.string)
+            .field("number", &self.number)
+            .field("comment", &self.comment)
+            .field("key", &self.key)
+            .field("section", &self.section)
+            .field("punctuation", &self.punctuation)
+            .finish()
+    }
+}
+
+/// The tokenizer state of the last style lookup.
+#[derive(Copy, Clone, Debug)]
+struct Cache<L> {
+    lexer: L,
+
+    /// The end of the token before `current`.
+    previous_end: usize,
+
+    /// The first token that ends after the last looked up offset.
+    current: Option<(usize, usize, Highlight)>,
+}
+
+impl<L: TokenSource> Cache<L> {
+    fn new(mut lexer: L) -> Self {
+        let current = lexer.next_token();
+
+        Self {
+            lexer,
+            previous_end: 0,
+            current,
+        }
+    }
+}
+
+/// The number of line starts at which the tokenizer state is kept.
+const CHECKPOINTS: usize = 4;
+
+/// The tokenizer states at the starts of the most recently tokenized lines.
+#[derive(Copy, Clone, Debug)]
+struct Checkpoints<L> {
+    caches: [Option<Cache<L>>; CHECKPOINTS],
+
+    /// The index of the checkpoint that is replaced next.
+    next: usize,
+}
+
+impl<L: TokenSource> Checkpoints<L> {
+    fn new() -> Self {
+        Self {
+            caches: [None; CHECKPOINTS],
+            next: 0,
+        }
+    }
+
+    /// Returns the checkpoint closest to `offset` that doesn't skip any tokens before it.
+    fn before(&self, offset: usize) -> Option<Cache<L>> {
+        self.caches
+            .iter()
+            .flatten()
+            .filter(|cache| cache.previous_end <= offset)
+            .max_by_key(|cache| cache.previous_end)
+            .copied()
+    }
+
+    /// Stores a checkpoint, replacing the oldest one if every slot is used.
+    fn push(&mut self, cache: Cache<L>) {
+        let known = self
+            .caches
+            .iter()
+            .flatten()
+            .any(|checkpoint| checkpoint.previous_end == cache.previous_end);
+
+        if !known {
+            self.caches[self.next] = Some(cache);
+            self.next = (self.next + 1) % CHECKPOINTS;
+        }
+    }
+}
+
+/// Looks up the highlighted tokens of a text by their offset.
+///
+/// The tokenizer continues from the last looked up position, so looking up the tokens from the
+/// beginning to the end of the text only tokenizes it once. The tokenizer state is also kept at
+/// the starts of the last few lines, so looking up a line again doesn't tokenize the text again
+/// from the beginning.
+#[derive(Clone)]
+struct Tokenizer<'a, L>
+where
+    L: TokenSource,
+{
+    text: &'a str,
+
+    /// The state of the tokenizer at the beginning of the text.
+    start: L,
+    cache: Cell<Cache<L>>,
+    checkpoints: Cell<Checkpoints<L>>,
+}
+
+impl<'a, L: TokenSource> Tokenizer<'a, L> {
+    fn new(text: &'a str, start: L) -> Self {
+        Self {
+            text,
+            start,
+            cache: Cell::new(Cache::new(start)),
+            checkpoints: Cell::new(Checkpoints::new()),
+        }
+    }
+
+    /// Returns the highlighted token that contains or follows `offset`.
+    fn token_at(&self, offset: usize) -> Option<(usize, usize, Highlight)> {
+        let mut cache = self.cache.get();
+        let mut checkpoints = self.checkpoints.get();
+        if offset < cache.previous_end {
+            cache = checkpoints
+                .before(offset)
+                .unwrap_or_else(|| Cache::new(self.start));
+        }
+
+        while let Some((_, end, _)) = cache.current {
+            if end > offset {
+                break;
+            }
+            cache.previous_end = end;
+            cache.current = cache.lexer.next_token();
+
+            // Keep the state before the first token of every line.
+            if let Some((next_start, _, _)) = cache.current {
+                if self.text[end..next_start].contains('\n') {
+                    checkpoints.push(cache);
+                }
+            }
+        }
+        self.cache.set(cache);
+        self.checkpoints.set(checkpoints);
+
+        cache.current
+    }
+}
+
+/// Spans that highlight the syntax of a text.
+///
+/// The text must have the same contents as the text of the text box that uses these spans. The
+/// hyphen that is inserted at a soft hyphen uses the style of the soft hyphen, and whitespace uses
+/// the style of its first character, so whitespace inside strings and comments is drawn using
+/// their theme style.
+///
+/// The text is tokenized while it is drawn. Drawing the text from the beginning to the end only
+/// tokenizes it once, and measuring and then drawing a line doesn't tokenize the text again from
+/// the beginning.
+#[derive(Clone)]
+pub struct Highlighted<'a, S>
+where
+    S: CharacterStyle,
+{
+    language: Language<'a>,
+    theme: &'a Theme<S>,
+    tokenizer: Tokenizer<'a, Lexer<'a>>,
+}
+
+impl<'a, S> Highlighted<'a, S>
+where
+    S: CharacterStyle,
+{
+    /// Creates new `Highlighted` spans for `text`.
+    #[inline]
+    #[must_use]
+    pub fn new(text: &'a str, language: Language<'a>, theme: &'a Theme<S>) -> Self {
+        Self {
+            language,
+            theme,
+            tokenizer: Tokenizer::new(text, Lexer::new(text, language)),
+        }
+    }
+}
+
+impl<S> fmt::Debug for Highlighted<'_, S>
+where
+    S: CharacterStyle + fmt::Debug,
+    S::Color: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Highlighted")
+            .field("text", &self.tokenizer.text)
+            .field("language", &self.language)
+            .field("theme", &self.theme)
+            .finish()
+    }
+}
+
+impl<S> Spans<S> for Highlighted<'_, S>
+where
+    S: CharacterStyle + Clone,
+{
+    #[inline]
+    fn style_at(&self, style: &S, offset: usize) -> (Option<S>, usize) {
+        match self.tokenizer.token_at(offset) {
+            Some((start, end, highlight)) if start <= offset => {
+                let mut style = style.clone();
+                self.theme.style(highlight).apply(&mut style);
+                (Some(style), end)
+            }
+            Some((start, _, _)) => (None, start),
+            None => (None, usize::MAX),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{
+            ascii::{FONT_10X20, FONT_6X9},
+            MonoTextStyle,
+        },
+        pixelcolor::Rgb888,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use super::*;
+    use crate::{
+        renderer::{styled_width, Annotated},
+        style::TextBoxStyle,
+        utils::test::size_for,
+        TextBox,
+    };
+
+    fn assert_tokens(text: &str, language: Language<'_>, expected: &[(&str, Highlight)]) {
+        let tokens = Tokens::new(text, language)
+            .map(|(range, highlight)| (&text[range], highlight))
+            .collect::<Vec<_>>();
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn json() {
+        assert_tokens(
+            "{\"a\\\"b\" : [1.5e3, -2], \"c\": \"d\", \"e\": null}",
+            Language::Json,
+            &[
+                ("{", Highlight::Punctuation),
+                ("\"a\\\"b\"", Highlight::Key),
+                (":", Highlight::Punctuation),
+                ("[", Highlight::Punctuation),
+                ("1.5e3", Highlight::Number),
+                (",", Highlight::Punctuation),
+                ("-2", Highlight::Number),
+                ("]", Highlight::Punctuation),
+                (",", Highlight::Punctuation),
+                ("\"c\"", Highlight::Key),
+                (":", Highlight::Punctuation),
+                ("\"d\"", Highlight::String),
+                (",", Highlight::Punctuation),
+                ("\"e\"", Highlight::Key),
+                (":", Highlight::Punctuation),
+                ("null", Highlight::Keyword),
+                ("}", Highlight::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn ini() {
+        assert_tokens(
+            "; settings\n[wifi]\nssid = home net \nchannel: 6\nenabled=yes\n",
+            Language::Ini,
+            &[
+                ("; settings", Highlight::Comment),
+                ("[wifi]", Highlight::Section),
+                ("ssid", Highlight::Key),
+                ("=", Highlight::Punctuation),
+                ("home net", Highlight::String),
+                ("channel", Highlight::Key),
+                (":", Highlight::Punctuation),
+                ("6", Highlight::Number),
+                ("enabled", Highlight::Key),
+                ("=", Highlight::Punctuation),
+                ("yes", Highlight::Keyword),
+            ],
+        );
+    }
+
+    #[test]
+    fn c_like() {
+        assert_tokens(
+            "int x = 0x10; // hex\n/* a\nb */ return 'c';",
+            Language::CLike(C_KEYWORDS),
+            &[
+                ("int", Highlight::Keyword),
+                ("=", Highlight::Punctuation),
+                ("0x10", Highlight::Number),
+                (";", Highlight::Punctuation),
+                ("// hex", Highlight::Comment),
+                ("/* a\nb */", Highlight::Comment),
+                ("return", Highlight::Keyword),
+                ("'c'", Highlight::String),
+                (";", Highlight::Punctuation),
+            ],
+        );
+    }
+
+    #[test]
+    fn unterminated_strings_end_at_the_end_of_the_line() {
+        assert_tokens(
+            "\"abc\n1",
+            Language::Json,
+            &[("\"abc", Highlight::String), ("1", Highlight::Number)],
+        );
+    }
+
+    #[test]
+    fn highlighted_text_is_drawn_like_annotated_text() {
+        let text = "int a = 5;";
+        let theme = Theme {
+            keyword: SpanStyle::new().text_color(Rgb888::BLUE),
+            number: SpanStyle::new().text_color(Rgb888::RED),
+            ..Theme::default()
+        };
+        let spans = [
+            (0..3, SpanStyle::new().text_color(Rgb888::BLUE)),
+            (8..9, SpanStyle::new().text_color(Rgb888::RED)),
+        ];
+
+        let base = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 1));
+
+        let mut display = MockDisplay::new();
+        let highlighted = Highlighted::new(text, Language::CLike(C_KEYWORDS), &theme);
+        TextBox::with_spans(text, bounds, base, TextBoxStyle::default(), &highlighted)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        let annotated = Annotated::new(&spans);
+        TextBox::with_spans(text, bounds, base, TextBoxStyle::default(), &annotated)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn whitespace_in_tokens_uses_the_theme_style() {
+        let text = "\"a b\" /* c d */";
+        let theme = Theme {
+            string: SpanStyle::new().background_color(Rgb888::RED),
+            comment: SpanStyle::new().background_color(Rgb888::BLUE),
+            ..Theme::default()
+        };
+        let highlighted = Highlighted::new(text, Language::CLike(C_KEYWORDS), &theme);
+
+        let mut display = MockDisplay::new();
+        TextBox::with_spans(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 1)),
+            MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+            TextBoxStyle::default(),
+            &highlighted,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        // The spaces inside the string and the comment have their background color, the space
+        // between them doesn't.
+        for x in 2 * 6..3 * 6 {
+            assert_eq!(display.get_pixel(Point::new(x, 0)), Some(Rgb888::RED));
+        }
+        for x in 5 * 6..6 * 6 {
+            assert_eq!(display.get_pixel(Point::new(x, 0)), None);
+        }
+        for x in 8 * 6..9 * 6 {
+            assert_eq!(display.get_pixel(Point::new(x, 0)), Some(Rgb888::BLUE));
+        }
+    }
+
+    #[test]
+    fn text_can_be_measured_in_any_order() {
+        let text = "1 a 22";
+        let theme = Theme {
+            number: SpanStyle::new().character_style(MonoTextStyle::new(&FONT_10X20, Rgb888::RED)),
+            ..Theme::default()
+        };
+        let highlighted = Highlighted::new(text, Language::Json, &theme);
+        let spans = Some(&highlighted as &dyn Spans<_>);
+        let base = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+
+        assert_eq!(styled_width(spans, &base, &text[4..], 4), 2 * 10);
+        assert_eq!(styled_width(spans, &base, &text[0..1], 0), 10);
+        assert_eq!(styled_width(spans, &base, &text[2..3], 2), 6);
+        assert_eq!(styled_width(spans, &base, text, 0), 10 + 6 + 6 + 6 + 2 * 10);
+    }
+
+    /// A lexer that counts how often the text is tokenized from the beginning.
+    #[derive(Copy, Clone)]
+    struct CountingLexer<'a> {
+        lexer: Lexer<'a>,
+        starts: &'a Cell<u32>,
+    }
+
+    impl TokenSource for CountingLexer<'_> {
+        fn next_token(&mut self) -> Option<(usize, usize, Highlight)> {
+            if self.lexer.pos == 0 {
+                self.starts.set(self.starts.get() + 1);
+            }
+
+            self.lexer.next_token()
+        }
+    }
+
+    #[test]
+    fn lines_are_not_tokenized_again_from_the_beginning() {
+        let text = "{\n  \"a\": 1,\n  \"b\": [\n    true,\n    null\n  ],\n  \"c\": \"d\"\n}";
+        let starts = Cell::new(0);
+        let tokenizer = Tokenizer::new(
+            text,
+            CountingLexer {
+                lexer: Lexer::new(text, Language::Json),
+                starts: &starts,
+            },
+        );
+
+        // Text boxes measure every line before they draw it.
+        let mut line_start = 0;
+        for line in text.split('\n') {
+            for _ in 0..2 {
+                for offset in line_start..line_start + line.len() {
+                    tokenizer.token_at(offset);
+                }
+            }
+            line_start += line.len() + 1;
+        }
+
+        assert_eq!(starts.get(), 1);
+
+        // Going back more lines than there are checkpoints starts from the beginning.
+        tokenizer.token_at(0);
+        assert_eq!(starts.get(), 2);
+    }
+}
@@ -0,0 +1,1598 @@
+//! Cached text layout.
+//!
+//! Drawing a [`TextBox`] measures every line of the text before rendering it, and some vertical
+//! alignments measure the whole text once more to find its height. A [`TextLayout`] stores the
+//! results of these measurements in a caller-provided buffer of [`LineRecord`]s, so they can be
+//! reused for drawing, hit testing and height queries until the text or the bounds change.
+//!
+//! [`TextBox`]: ../struct.TextBox.html
+//! [`TextLayout`]: struct.TextLayout.html
+//! [`LineRecord`]: struct.LineRecord.html
+#[cfg(feature = "ansi")]
+use core::convert::Infallible;
+use core::ops::Range;
+
+use crate::{
+    alignment::{HorizontalTextAlignment, VerticalTextAlignment},
+    parser::{Parser, Token},
+    rendering::{cursor::Cursor, rotation::RotatedDrawTarget},
+    style::{color::Rgb, height_mode::HeightMode, LineMeasurement, Rotation, WritingMode},
+    TextBox,
+};
+#[cfg(feature = "ansi")]
+use crate::{
+    renderer::{styled_width, Spans},
+    rendering::{
+        ansi::Sgr,
+        line_iter::{ElementHandler, LineElementParser},
+    },
+};
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Dimensions, Point, Size},
+    primitives::{Primitive, PrimitiveStyle, Rectangle},
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+/// The token that is carried over from the previous line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Carried {
+    /// The line starts with the next token of the parser.
+    Nothing,
+
+    /// The line starts with the remainder of a wrapped word, which is `len` bytes long and ends
+    /// where the line starts.
+    Word { len: usize },
+
+    /// The line starts with the hyphen of a soft hyphen that didn't fit into the previous line.
+    Hyphen,
+}
+
+impl Default for Carried {
+    #[inline]
+    fn default() -> Self {
+        Carried::Nothing
+    }
+}
+
+impl Carried {
+    /// Encodes a carried token.
+    ///
+    /// `start` is the byte offset into `text` where the parser currently is.
+    pub(crate) fn new(text: &str, start: usize, token: &Option<Token<'_>>) -> Self {
+        match token {
+            Some(Token::Word(word)) => {
+                let text_end = text.as_ptr() as usize + start;
+                let word_end = word.as_ptr() as usize + word.len();
+
+                if word_end == text_end {
+                    Carried::Word { len: word.len() }
+                } else {
+                    Carried::Hyphen
+                }
+            }
+
+            // Tokens that end a line aren't carried over to the next one.
+            _ => Carried::Nothing,
+        }
+    }
+
+    /// Decodes a carried token.
+    fn token(self, text: &str, start: usize) -> Option<Token<'_>> {
+        match self {
+            Carried::Nothing => None,
+            Carried::Word { len } => Some(Token::Word(&text[start - len..start])),
+            Carried::Hyphen => Some(Token::Word("-")),
+        }
+    }
+}
+
+const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// Updates a 32 bit FNV-1a hash with the given bytes.
+fn fnv1a(hash: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(hash, |hash, b| {
+        (hash ^ u32::from(*b)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Updates `hash` with the escape sequences in `line`.
+///
+/// Escape sequences change the character style of the following text, so their hash can be used
+/// to detect style changes.
+#[cfg(feature = "ansi")]
+fn escape_sequence_hash(mut hash: u32, line: &str) -> u32 {
+    let mut parser = Parser::parse(line);
+    loop {
+        let start = line.len() - parser.as_str().len();
+        match parser.next() {
+            Some(Token::EscapeSequence(_)) => {
+                let end = line.len() - parser.as_str().len();
+                hash = fnv1a(hash, &line.as_bytes()[start..end]);
+            }
+            Some(_) => {}
+            None => return hash,
+        }
+    }
+}
+
+#[cfg(not(feature = "ansi"))]
+fn escape_sequence_hash(hash: u32, _line: &str) -> u32 {
+    hash
+}
+
+/// Layout information of a single line of text.
+///
+/// Use `LineRecord::default()` to initialize the buffer of a [`TextLayout`].
+///
+/// [`TextLayout`]: struct.TextLayout.html
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LineRecord {
+    /// Byte offset of the parser at the start of the line.
+    start: usize,
+
+    /// The token that is carried over from the previous line.
+    carried: Carried,
+
+    /// Byte offset of the parser at the end of the line.
+    end: usize,
+
+    /// Vertical position of the line, relative to the top of the text.
+    y: i32,
+
+    /// The measurement of the line.
+    measurement: LineMeasurement,
+
+    /// Whether the line is the first line of a paragraph.
+    paragraph_start: bool,
+
+    /// The width of the preceding lines that end with a carriage return.
+    cr_width: Option<u32>,
+
+    /// The height of the preceding lines that end with a carriage return.
+    cr_height: u32,
+
+    /// The height of the text above the line.
+    height: u32,
+
+    /// Whether a double underline is active at the start of the line.
+    double_underline: bool,
+
+    /// Hash of the escape sequences that precede the line.
+    style_hash: u32,
+
+    /// Hash of the contents of the line, including the style at the start of the line.
+    hash: u32,
+}
+
+impl LineRecord {
+    /// Returns the vertical position of the line, relative to the top of the text.
+    ///
+    /// Lines that are separated by a carriage return have the same vertical position.
+    #[inline]
+    #[must_use]
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    /// Returns the width of the line in pixels.
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.measurement.width
+    }
+
+    /// Returns the height of the line in pixels.
+    ///
+    /// Lines that are separated by a carriage return are as tall as the tallest of them.
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.measurement.height().max(self.cr_height)
+    }
+
+    /// Returns the range of bytes of the text that make up the line.
+    ///
+    /// The range includes the whitespace and the line break characters at the end of the line.
+    #[inline]
+    #[must_use]
+    pub fn byte_range(&self) -> Range<usize> {
+        let carried = match self.carried {
+            Carried::Word { len } => len,
+            _ => 0,
+        };
+
+        self.start - carried..self.end
+    }
+
+    /// Computes the hash of the contents and the style of the line which ends at `end`.
+    fn content_hash(&self, text: &str, end: usize) -> u32 {
+        let hash = match self.carried {
+            Carried::Hyphen => fnv1a(self.style_hash, &[0xAD]),
+            _ => self.style_hash,
+        };
+
+        let carried = match self.carried {
+            Carried::Word { len } => len,
+            _ => 0,
+        };
+
+        fnv1a(hash, &text.as_bytes()[self.start - carried..end])
+    }
+
+    /// Returns `true` if the line looks the same as `other`, without considering its position.
+    fn same_contents(&self, other: &LineRecord) -> bool {
+        self.hash == other.hash
+            && self.measurement == other.measurement
+            && self.paragraph_start == other.paragraph_start
+    }
+
+    /// Returns the text of the line that is consumed from the parser.
+    pub(crate) fn line_str<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.start..self.end]
+    }
+
+    /// Returns the measurement of the line.
+    pub(crate) fn measurement(&self) -> LineMeasurement {
+        self.measurement
+    }
+
+    /// Returns whether the line starts at the given parser state.
+    pub(crate) fn starts_at(
+        &self,
+        text: &str,
+        parser: &Parser<'_>,
+        carried: &Option<Token<'_>>,
+    ) -> bool {
+        let start = text.len() - parser.as_str().len();
+
+        self.start == start && self.carried == Carried::new(text, start, carried)
+    }
+}
+
+/// A text box with a cached layout.
+///
+/// `TextLayout` measures the text of a [`TextBox`] once, and stores the line information in a
+/// caller-provided buffer. Drawing a `TextLayout` doesn't need to measure the lines or the height
+/// of the text again.
+///
+/// If the buffer is too small to hold every line, the lines that don't fit are measured while
+/// drawing, as if the text box was drawn directly.
+///
+/// The layout must be recomputed using [`relayout`] when the text or the bounds change.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{
+///     layout::{LineRecord, TextLayout},
+///     prelude::*,
+/// };
+///
+/// let character_style = MonoTextStyleBuilder::new()
+///     .font(&FONT_6X9)
+///     .text_color(BinaryColor::On)
+///     .build();
+///
+/// let textbox_style = TextBoxStyleBuilder::new()
+///     .vertical_alignment(CenterAligned)
+///     .build();
+///
+/// let text_box = TextBox::with_textbox_style(
+///     "Hello, World!",
+///     Rectangle::new(Point::zero(), Size::new(48, 32)),
+///     character_style,
+///     textbox_style,
+/// );
+///
+/// let mut buffer = [LineRecord::default(); 4];
+/// let layout = TextLayout::new(text_box, &mut buffer);
+///
+/// assert_eq!(2, layout.lines().len());
+/// assert_eq!(18, layout.text_height());
+///
+/// let mut display: MockDisplay<BinaryColor> = MockDisplay::new();
+/// layout.draw(&mut display).unwrap();
+/// ```
+///
+/// [`TextBox`]: ../struct.TextBox.html
+/// [`relayout`]: #method.relayout
+#[derive(Debug)]
+pub struct TextLayout<'a, 'b, S, A, V, H> {
+    text_box: TextBox<'a, S, A, V, H>,
+    buffer: &'b mut [LineRecord],
+    state: LayoutState,
+}
+
+/// The state of a [`TextLayout`], without the text and the line buffer.
+///
+/// A `LayoutState` doesn't borrow the text, which makes it possible to append to the text and
+/// extend the layout using [`TextLayout::extend`].
+///
+/// [`TextLayout`]: struct.TextLayout.html
+/// [`TextLayout::extend`]: struct.TextLayout.html#method.extend
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LayoutState {
+    /// Length of the laid out text, in bytes.
+    text_len: usize,
+
+    /// Number of lines stored in the buffer.
+    line_count: usize,
+
+    /// Number of lines of the text.
+    total_lines: usize,
+
+    /// Height of the text.
+    text_height: u32,
+
+    /// The last two lines of the text.
+    tail: [LineRecord; 2],
+}
+
+impl<'a, 'b, S, A, V, H> TextLayout<'a, 'b, S, A, V, H>
+where
+    S: TextRenderer + CharacterStyle,
+    A: HorizontalTextAlignment,
+    V: VerticalTextAlignment,
+    H: HeightMode,
+{
+    /// Measures the text box and stores its layout in `buffer`.
+    #[inline]
+    #[must_use]
+    pub fn new(text_box: TextBox<'a, S, A, V, H>, buffer: &'b mut [LineRecord]) -> Self {
+        Self::extend(text_box, buffer, LayoutState::default())
+    }
+
+    /// Extends a previously computed layout after text has been appended to the end.
+    ///
+    /// `state` and `buffer` must come from a layout of a text box with the same style and bounds,
+    /// whose text is the beginning of the current text. Only the last two lines of the previous
+    /// layout and the appended text are measured.
+    ///
+    /// If `state` belongs to a longer text, the whole text is measured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    /// };
+    /// use embedded_text::{
+    ///     layout::{LayoutState, LineRecord, TextLayout},
+    ///     prelude::*,
+    /// };
+    ///
+    /// let character_style = MonoTextStyleBuilder::new()
+    ///     .font(&FONT_6X9)
+    ///     .text_color(BinaryColor::On)
+    ///     .build();
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(96, 60));
+    ///
+    /// let mut buffer = [LineRecord::default(); 8];
+    /// let mut state = LayoutState::default();
+    ///
+    /// let mut console = String::new();
+    /// for line in &["first line\n", "second line\n", "third"] {
+    ///     console.push_str(line);
+    ///
+    ///     let text_box = TextBox::new(&console, bounds, character_style);
+    ///     let layout = TextLayout::extend(text_box, &mut buffer, state);
+    ///
+    ///     // draw the layout here
+    ///
+    ///     state = layout.state();
+    /// }
+    ///
+    /// assert_eq!(3 * 9, state.text_height());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn extend(
+        text_box: TextBox<'a, S, A, V, H>,
+        buffer: &'b mut [LineRecord],
+        state: LayoutState,
+    ) -> Self {
+        let mut layout = Self {
+            text_box,
+            buffer,
+            state,
+        };
+
+        layout.measure();
+
+        layout
+    }
+
+    /// Replaces the text box and recomputes the layout.
+    #[inline]
+    pub fn relayout(&mut self, text_box: TextBox<'a, S, A, V, H>) {
+        self.text_box = text_box;
+        self.state = LayoutState::default();
+        self.measure();
+    }
+
+    fn measure(&mut self) {
+        let text = self.text_box.text;
+        let style = &self.text_box.style;
+        let character_style = &self.text_box.character_style;
+
+        let text_area = self.text_box.text_area();
+        let mut cursor = Cursor::new(text_area, character_style, style);
+
+        // Appending text may change how the last line, and the whitespace at the end of the line
+        // before it are laid out. Lines before these two are not affected.
+        let previous = self.state;
+        let restart = previous.total_lines >= 2
+            && previous.text_len <= text.len()
+            && previous.line_count <= self.buffer.len();
+
+        let (
+            first_line,
+            mut parser,
+            mut carried,
+            mut cr_width,
+            mut cr_height,
+            mut height,
+            mut double_underline,
+            mut style_hash,
+        ) = if restart {
+            let record = previous.tail[0];
+            cursor.y = text_area.top_left.y + record.y;
+            cursor.set_paragraph_start(record.paragraph_start);
+
+            (
+                previous.total_lines - 2,
+                Parser::parse_at(text, record.start),
+                record.carried.token(text, record.start),
+                record.cr_width,
+                record.cr_height,
+                record.height,
+                record.double_underline,
+                record.style_hash,
+            )
+        } else {
+            (
+                0,
+                Parser::parse(text),
+                None,
+                None,
+                0,
+                0,
+                false,
+                FNV_OFFSET_BASIS,
+            )
+        };
+
+        let mut line_index = first_line;
+        let mut tail = if restart {
+            previous.tail
+        } else {
+            [LineRecord::default(); 2]
+        };
+        while carried.is_some() || !parser.is_empty() {
+            let start = text.len() - parser.as_str().len();
+            let record = LineRecord {
+                start,
+                carried: Carried::new(text, start, &carried),
+                y: cursor.y - text_area.top_left.y,
+                paragraph_start: cursor.paragraph_start(),
+                cr_width,
+                cr_height,
+                height,
+                double_underline,
+                style_hash,
+                ..LineRecord::default()
+            };
+
+            let measurement = style.measure_line(
+                character_style,
+                self.text_box.spans,
+                &mut parser,
+                &mut carried,
+                &mut double_underline,
+                cursor.line().line_width(),
+            );
+
+            let end = text.len() - parser.as_str().len();
+            let record = LineRecord {
+                end,
+                measurement,
+                hash: record.content_hash(text, end),
+                ..record
+            };
+            cursor.set_line_height(record.height());
+            style_hash = escape_sequence_hash(style_hash, &text[start..end]);
+
+            if let Some(slot) = self.buffer.get_mut(line_index) {
+                *slot = record;
+            }
+            tail = [tail[1], record];
+            line_index += 1;
+
+            // The height is calculated the same way as in `TextBoxStyle::measure_text_height`.
+            if carried == Some(Token::CarriageReturn) {
+                cr_width =
+                    Some(cr_width.map_or(measurement.width, |w: u32| w.max(measurement.width)));
+                cr_height = record.height();
+            } else {
+                let line_width = cr_width
+                    .take()
+                    .map_or(measurement.width, |w| w.max(measurement.width));
+                cr_height = 0;
+
+                if line_width > 0 || carried == Some(Token::NewLine) {
+                    height = record.y as u32 + record.height();
+                }
+            }
+
+            match carried {
+                Some(Token::CarriageReturn) => {}
+                Some(Token::NewLine) => cursor.new_paragraph(),
+                _ => cursor.new_line(),
+            }
+        }
+
+        if restart && line_index < first_line + 2 {
+            // The tail of the text can't be determined without the preceding line.
+            self.state = LayoutState::default();
+            self.measure();
+            return;
+        }
+
+        self.state = LayoutState {
+            text_len: text.len(),
+            line_count: line_index.min(self.buffer.len()),
+            total_lines: line_index,
+            text_height: height,
+            tail,
+        };
+    }
+
+    /// Returns the vertical offset of the text inside the text area.
+    fn vertical_offset(&self) -> i32 {
+        V::vertical_offset(
+            self.state.text_height,
+            self.text_box.text_area().size.height,
+        ) + self.text_box.animation.scroll_offset
+    }
+
+    /// Returns the index of the line at the given point.
+    ///
+    /// Returns `None` if the point is above or below the text, or if the line at the point is not
+    /// stored in the buffer. Points between two lines belong to the line above.
+    ///
+    /// `point` is given in display coordinates, even if the text is rotated.
+    #[inline]
+    #[must_use]
+    pub fn line_at(&self, point: Point) -> Option<usize> {
+        let point = self
+            .text_box
+            .style
+            .rotation
+            .to_text(point, &self.text_box.bounds);
+
+        let top = self.text_box.text_area().top_left.y + self.vertical_offset();
+        let y = point.y - top;
+        if y < 0 {
+            return None;
+        }
+
+        let lines = self.lines();
+        let index = lines.iter().rposition(|line| line.y <= y)?;
+
+        let is_last = index + 1 == lines.len();
+        if is_last && y >= lines[index].y + lines[index].height() as i32 {
+            return None;
+        }
+
+        Some(index)
+    }
+}
+
+impl<'a, 'b, S, A, V, H> TextLayout<'a, 'b, S, A, V, H> {
+    /// Returns the text box.
+    #[inline]
+    #[must_use]
+    pub fn text_box(&self) -> &TextBox<'a, S, A, V, H> {
+        &self.text_box
+    }
+
+    /// Returns the stored lines.
+    #[inline]
+    #[must_use]
+    pub fn lines(&self) -> &[LineRecord] {
+        &self.buffer[..self.state.line_count]
+    }
+
+    /// Returns `true` if every line of the text is stored in the buffer.
+    #[inline]
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.state.is_complete()
+    }
+
+    /// Returns the height of the text.
+    ///
+    /// The returned value is the same as the result of [`TextBoxStyle::measure_text_height`],
+    /// measured using the width of the text area.
+    ///
+    /// [`TextBoxStyle::measure_text_height`]: ../style/struct.TextBoxStyle.html#method.measure_text_height
+    #[inline]
+    #[must_use]
+    pub fn text_height(&self) -> u32 {
+        self.state.text_height
+    }
+
+    /// Returns the state of the layout.
+    ///
+    /// The state can be used to extend the layout after text has been appended.
+    #[inline]
+    #[must_use]
+    pub fn state(&self) -> LayoutState {
+        self.state
+    }
+}
+
+impl LayoutState {
+    /// Returns `true` if every line of the text is stored in the buffer.
+    #[inline]
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.line_count == self.total_lines
+    }
+
+    /// Returns the height of the text.
+    #[inline]
+    #[must_use]
+    pub fn text_height(&self) -> u32 {
+        self.text_height
+    }
+}
+
+impl<S, A, V, H> Dimensions for TextLayout<'_, '_, S, A, V, H> {
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.text_box.bounds
+    }
+}
+
+impl<'a, F, A, V, H> Drawable for TextLayout<'a, '_, F, A, V, H>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb>,
+    A: HorizontalTextAlignment,
+    V: VerticalTextAlignment,
+    H: HeightMode,
+{
+    type Color = <F as CharacterStyle>::Color;
+    type Output = &'a str;
+
+    #[inline]
+    fn draw<D: DrawTarget<Color = Self::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        match self.text_box.style.rotation {
+            Rotation::None => self.draw_text(display),
+            rotation => self.draw_text(&mut RotatedDrawTarget::new(
+                display,
+                self.text_box.bounds,
+                rotation,
+            )),
+        }
+    }
+}
+
+impl<'a, F, A, V, H> TextLayout<'a, '_, F, A, V, H>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb>,
+    A: HorizontalTextAlignment,
+    V: VerticalTextAlignment,
+    H: HeightMode,
+{
+    /// Draws the frame and the text in the coordinate system of the text.
+    fn draw_text<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        let text_box = &self.text_box;
+        if text_box.style.writing_mode == WritingMode::Vertical {
+            // The layout only stores horizontal lines.
+            return text_box.draw_text(display);
+        }
+
+        text_box.draw_frame(display)?;
+
+        let mut cursor = Cursor::new(
+            text_box.text_area(),
+            &text_box.character_style,
+            &text_box.style,
+        );
+        cursor.y += self.vertical_offset();
+
+        text_box.draw_lines(display, cursor, self.lines(), |_| true)
+    }
+
+    /// Draws the lines that changed since `previous` was drawn.
+    ///
+    /// A line is redrawn if its contents, its style or its position changed, or if it is not
+    /// stored in the buffer. Every line is redrawn if the revealed part of the text or the phase
+    /// of blinking text changed. Before drawing, the area of the changed and removed lines is cleared
+    /// using the background color of the text box. The background and the border of the text box
+    /// are not drawn.
+    ///
+    /// Both layouts must have the same bounds, character style and text box style.
+    ///
+    /// *Note:* Vacated areas are only cleared if the text box has a background color.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mock_display::MockDisplay,
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+    ///     pixelcolor::Rgb888,
+    ///     prelude::*,
+    /// };
+    /// use embedded_text::{
+    ///     layout::{LineRecord, TextLayout},
+    ///     prelude::*,
+    ///     style::color::Rgb,
+    /// };
+    ///
+    /// let character_style = MonoTextStyleBuilder::new()
+    ///     .font(&FONT_6X9)
+    ///     .text_color(Rgb888::WHITE)
+    ///     .build();
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .background_color(Rgb::new(0, 0, 0))
+    ///     .build();
+    /// let bounds = Rectangle::new(Point::zero(), Size::new(60, 27));
+    ///
+    /// let mut display: MockDisplay<Rgb888> = MockDisplay::new();
+    /// display.set_allow_overdraw(true);
+    ///
+    /// let mut previous_buffer = [LineRecord::default(); 3];
+    /// let text_box = TextBox::with_textbox_style("Volume\n10%", bounds, character_style, style);
+    /// let previous = TextLayout::new(text_box, &mut previous_buffer);
+    /// previous.draw(&mut display).unwrap();
+    ///
+    /// // Only the second line is cleared and redrawn.
+    /// let mut buffer = [LineRecord::default(); 3];
+    /// let text_box = TextBox::with_textbox_style("Volume\n20%", bounds, character_style, style);
+    /// let layout = TextLayout::new(text_box, &mut buffer);
+    /// layout.draw_changes(&previous, &mut display).unwrap();
+    /// ```
+    #[inline]
+    pub fn draw_changes<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        previous: &TextLayout<'_, '_, F, A, V, H>,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        match self.text_box.style.rotation {
+            Rotation::None => self.draw_changed_text(previous, display),
+            rotation => self.draw_changed_text(
+                previous,
+                &mut RotatedDrawTarget::new(display, self.text_box.bounds, rotation),
+            ),
+        }
+    }
+
+    /// Draws the changed lines in the coordinate system of the text.
+    fn draw_changed_text<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        previous: &TextLayout<'_, '_, F, A, V, H>,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        let text_box = &self.text_box;
+        if text_box.style.writing_mode == WritingMode::Vertical {
+            return text_box.draw_text(display);
+        }
+
+        let text_area = text_box.text_area();
+        let offset = self.vertical_offset();
+        let previous_offset = previous.vertical_offset();
+
+        // Lines that are not stored in the buffer are assumed to use the default line height.
+        let line_spacing = |height| text_box.style.line_height.to_absolute(height);
+        let overlapping = core::iter::once(text_box.character_style.line_height())
+            .chain(self.lines().iter().map(LineRecord::height))
+            .chain(previous.lines().iter().map(LineRecord::height))
+            .any(|height| line_spacing(height) < height);
+        // Blinking and revealed text is not tracked per line, so every line is redrawn if the
+        // blink phase or the revealed part of the text changed. Blinking text changes at multiples
+        // of the blink interval.
+        let interval = text_box.style.blink_interval.max(1);
+        let animation = &text_box.animation;
+        let previous_animation = &previous.text_box.animation;
+        let animation_changed = animation.reveal != previous_animation.reveal
+            || animation.tick / interval != previous_animation.tick / interval;
+
+        let diff = LineDiff {
+            lines: self.lines(),
+            previous: previous.lines(),
+            offset: offset - previous_offset,
+            overlapping,
+            animation_changed,
+        };
+
+        if let Some(color) = text_box.style.background_color {
+            let mut display = display.clipped(&text_area);
+            let style = PrimitiveStyle::with_fill(color.into());
+            let top = text_area.top_left.y + previous_offset;
+
+            for (index, line) in previous.lines().iter().enumerate() {
+                if diff.clear_row(index) {
+                    let band_height = line.height().min(line_spacing(line.height()));
+                    Rectangle::new(
+                        Point::new(text_area.top_left.x, top + line.y),
+                        Size::new(text_area.size.width, band_height),
+                    )
+                    .into_styled(style)
+                    .draw(&mut display)?;
+                }
+            }
+
+            if !previous.is_complete() {
+                // Lines that are not stored can be anywhere below the last stored line.
+                let y = previous
+                    .lines()
+                    .last()
+                    .map_or(top, |line| top + line.y + line.height() as i32);
+                let bottom = text_area.top_left.y + text_area.size.height as i32;
+
+                Rectangle::new(
+                    Point::new(text_area.top_left.x, y),
+                    Size::new(text_area.size.width, (bottom - y).max(0) as u32),
+                )
+                .into_styled(style)
+                .draw(&mut display)?;
+            }
+        }
+
+        let mut cursor = Cursor::new(text_area, &text_box.character_style, &text_box.style);
+        cursor.y += offset;
+
+        text_box.draw_lines(display, cursor, self.lines(), |index| {
+            diff.redraw_row(index)
+        })
+    }
+}
+
+#[cfg(feature = "ansi")]
+impl<'a, F, A, V, H> TextLayout<'a, '_, F, A, V, H>
+where
+    F: TextRenderer + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb>,
+    A: HorizontalTextAlignment,
+    V: VerticalTextAlignment,
+    H: HeightMode,
+{
+    /// Calls `callback` with the URI and the area of every hyperlink in the text.
+    ///
+    /// Hyperlinks are marked using OSC 8 escape sequences: the text between
+    /// `\x1b]8;;URI\x1b\\` and `\x1b]8;;\x1b\\` is a link to `URI`. A link that is wrapped
+    /// into more lines is reported once for each line it appears in.
+    ///
+    /// Only the lines that are stored in the buffer are reported. Areas are given in display
+    /// coordinates, even if the text is rotated. Hyperlinks in vertical text are not reported.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use embedded_graphics::{
+    ///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+    ///     pixelcolor::BinaryColor,
+    ///     prelude::*,
+    /// };
+    /// use embedded_text::{
+    ///     layout::{LineRecord, TextLayout},
+    ///     prelude::*,
+    /// };
+    ///
+    /// let character_style = MonoTextStyle::new(&FONT_6X9, BinaryColor::On);
+    /// let text_box = TextBox::new(
+    ///     "See \x1b]8;;help:wifi\x1b\\Wi-Fi settings\x1b]8;;\x1b\\.",
+    ///     Rectangle::new(Point::zero(), Size::new(60, 27)),
+    ///     character_style,
+    /// );
+    ///
+    /// let mut buffer = [LineRecord::default(); 3];
+    /// let layout = TextLayout::new(text_box, &mut buffer);
+    ///
+    /// layout.hyperlinks(|uri, area| {
+    ///     assert_eq!(uri, "help:wifi");
+    ///     // The link is wrapped into two lines.
+    ///     assert!(area.top_left.y == 0 || area.top_left.y == 9);
+    /// });
+    ///
+    /// assert_eq!(layout.hyperlink_at(Point::new(30, 4)), Some("help:wifi"));
+    /// ```
+    #[inline]
+    pub fn hyperlinks(&self, mut callback: impl FnMut(&'a str, Rectangle)) {
+        let text_box = &self.text_box;
+        if text_box.style.writing_mode == WritingMode::Vertical {
+            return;
+        }
+
+        let text = text_box.text;
+        let mut character_style = text_box.character_style.clone();
+        let mut link = None;
+
+        let mut cursor = Cursor::new(
+            text_box.text_area(),
+            &text_box.character_style,
+            &text_box.style,
+        );
+        let top = cursor.y + self.vertical_offset();
+
+        for record in self.lines() {
+            cursor.y = top + record.y;
+            cursor.set_paragraph_start(record.paragraph_start);
+
+            let mut line_cursor = cursor.line();
+            let (left, space_config) = A::place_line(
+                record.line_str(text),
+                &character_style,
+                record.measurement(),
+            );
+            line_cursor.move_cursor(left as i32).ok();
+
+            // Links that continue from the previous line start at the beginning of this one.
+            let Point { x, y: line_top } = line_cursor.pos();
+            if let Some((_, start)) = link.as_mut() {
+                *start = x;
+            }
+
+            let height = record.height();
+            let mut handler = HyperlinkElementHandler {
+                text,
+                style: &mut character_style,
+                spans: text_box.spans,
+                link: &mut link,
+                x,
+                text_end: x,
+                report: |uri, start: i32, end: i32| {
+                    let area = Rectangle::new(
+                        Point::new(start, line_top),
+                        Size::new((end - start) as u32, height),
+                    );
+                    callback(uri, self.to_display(area));
+                },
+            };
+
+            let mut parser = Parser::parse_at(text, record.start);
+            LineElementParser::<'_, '_, _, A>::new(
+                &mut parser,
+                line_cursor,
+                space_config,
+                record.carried.token(text, record.start),
+            )
+            .process(&mut handler)
+            .ok();
+
+            // Whitespace at the end of the line is not part of the link.
+            let text_end = handler.text_end;
+            handler.report_open_link(text_end);
+        }
+    }
+
+    /// Returns the URI of the hyperlink at the given point.
+    ///
+    /// `point` is given in display coordinates, even if the text is rotated. See [`hyperlinks`]
+    /// for the areas that are considered to be part of a hyperlink.
+    ///
+    /// [`hyperlinks`]: #method.hyperlinks
+    #[inline]
+    #[must_use]
+    pub fn hyperlink_at(&self, point: Point) -> Option<&'a str> {
+        let mut found = None;
+        self.hyperlinks(|uri, area| {
+            if found.is_none() && area.contains(point) {
+                found = Some(uri);
+            }
+        });
+
+        found
+    }
+
+    /// Converts an area in the coordinate system of the text to display coordinates.
+    fn to_display(&self, area: Rectangle) -> Rectangle {
+        let rotation = self.text_box.style.rotation;
+        let bounds = &self.text_box.bounds;
+
+        match area.bottom_right() {
+            Some(bottom_right) => Rectangle::with_corners(
+                rotation.to_display(area.top_left, bounds),
+                rotation.to_display(bottom_right, bounds),
+            ),
+            None => area,
+        }
+    }
+}
+
+/// Element handler that reports the parts of hyperlinks in a line.
+#[cfg(feature = "ansi")]
+struct HyperlinkElementHandler<'a, 's, S, R> {
+    text: &'a str,
+    style: &'s mut S,
+    spans: Option<&'a dyn Spans<S>>,
+
+    /// The URI and the horizontal start position of the current link.
+    link: &'s mut Option<(&'a str, i32)>,
+
+    /// Horizontal position of the cursor.
+    x: i32,
+
+    /// Horizontal position of the end of the last printed characters.
+    text_end: i32,
+
+    /// Called with the URI and the horizontal range of each part of a link.
+    report: R,
+}
+
+#[cfg(feature = "ansi")]
+impl<'a, S, R> HyperlinkElementHandler<'a, '_, S, R>
+where
+    R: FnMut(&'a str, i32, i32),
+{
+    /// Reports the part of the current link that ends at `end`.
+    fn report_open_link(&mut self, end: i32) {
+        if let Some((uri, start)) = *self.link {
+            if end > start {
+                (self.report)(uri, start, end);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ansi")]
+impl<'a, S, R> ElementHandler for HyperlinkElementHandler<'a, '_, S, R>
+where
+    S: TextRenderer + CharacterStyle,
+    <S as CharacterStyle>::Color: From<Rgb>,
+    R: FnMut(&'a str, i32, i32),
+{
+    type Error = Infallible;
+
+    fn measure(&self, st: &str, offset: usize) -> u32 {
+        styled_width(self.spans, self.style, st, offset)
+    }
+
+    fn whitespace(&mut self, width: u32, _offset: usize) -> Result<(), Self::Error> {
+        self.x += width as i32;
+        Ok(())
+    }
+
+    fn printed_characters(
+        &mut self,
+        _st: &str,
+        width: u32,
+        _offset: usize,
+    ) -> Result<(), Self::Error> {
+        self.x += width as i32;
+        self.text_end = self.x;
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.x += by;
+        Ok(())
+    }
+
+    fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
+        sgr.apply(self.style);
+        Ok(())
+    }
+
+    fn hyperlink(&mut self, uri: &str) -> Result<(), Self::Error> {
+        self.report_open_link(self.x);
+
+        *self.link = if uri.is_empty() {
+            None
+        } else {
+            // The URI is a slice of the text, so its offset can be calculated.
+            let offset = uri.as_ptr() as usize - self.text.as_ptr() as usize;
+            Some((&self.text[offset..offset + uri.len()], self.x))
+        };
+
+        Ok(())
+    }
+}
+
+/// Compares the lines of two layouts.
+struct LineDiff<'l> {
+    lines: &'l [LineRecord],
+    previous: &'l [LineRecord],
+
+    /// Difference of the vertical offsets of the two layouts.
+    offset: i32,
+
+    /// Whether lines are drawn over the line below them.
+    overlapping: bool,
+
+    /// Whether the animation changed in a way that may change how any line looks.
+    animation_changed: bool,
+}
+
+impl LineDiff<'_> {
+    /// Returns `true` if the line looks the same and is at the same position in both layouts.
+    fn line_unchanged(&self, index: usize) -> bool {
+        if self.animation_changed {
+            return false;
+        }
+
+        match (self.lines.get(index), self.previous.get(index)) {
+            (Some(line), Some(previous)) => {
+                line.same_contents(previous) && line.y + self.offset == previous.y
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the indices of the lines that are drawn in the same row as the given line, in
+    /// either of the layouts.
+    fn row(&self, index: usize) -> Range<usize> {
+        let current = row(self.lines, index);
+        let previous = row(self.previous, index);
+
+        current.start.min(previous.start)..current.end.max(previous.end)
+    }
+
+    fn row_changed(&self, index: usize) -> bool {
+        !self.row(index).all(|index| self.line_unchanged(index))
+    }
+
+    /// Returns `true` if the area of the given row needs to be cleared.
+    fn clear_row(&self, index: usize) -> bool {
+        if self.row_changed(index) {
+            return true;
+        }
+
+        // Overlapping lines are partially drawn in the row below them.
+        let start = self.row(index).start;
+        self.overlapping && start > 0 && self.row_changed(start - 1)
+    }
+
+    /// Returns `true` if the given row needs to be drawn.
+    fn redraw_row(&self, index: usize) -> bool {
+        if self.clear_row(index) {
+            return true;
+        }
+
+        // Overlapping lines need to be redrawn if the row below them was cleared.
+        let end = self.row(index).end;
+        self.overlapping && end < self.lines.len().max(self.previous.len()) && self.clear_row(end)
+    }
+}
+
+/// Returns the indices of the lines that are drawn in the same row as the given line.
+fn row(lines: &[LineRecord], index: usize) -> Range<usize> {
+    match lines.get(index) {
+        Some(line) => {
+            let start = lines[..index]
+                .iter()
+                .rposition(|other| other.y != line.y)
+                .map_or(0, |i| i + 1);
+            let end = lines[index..]
+                .iter()
+                .position(|other| other.y != line.y)
+                .map_or(lines.len(), |i| index + i);
+
+            start..end
+        }
+        None => index..index + 1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::{BinaryColor, Rgb888},
+        prelude::*,
+        primitives::Rectangle,
+        text::renderer::{CharacterStyle, TextRenderer},
+    };
+
+    use crate::{
+        alignment::{BottomAligned, HorizontalTextAlignment, Justified, VerticalTextAlignment},
+        layout::{LineRecord, TextLayout},
+        style::{
+            color::Rgb,
+            height_mode::{Exact, HeightMode},
+            vertical_overdraw::FullRowsOnly,
+            TextBoxStyleBuilder,
+        },
+        utils::test::size_for,
+        TextBox,
+    };
+
+    fn assert_same_output<S, A, V, H>(text_box: TextBox<'_, S, A, V, H>, buffer_size: usize)
+    where
+        S: TextRenderer<Color = BinaryColor> + CharacterStyle<Color = BinaryColor>,
+        A: HorizontalTextAlignment,
+        V: VerticalTextAlignment,
+        H: HeightMode,
+    {
+        let mut expected = MockDisplay::new();
+        expected.set_allow_overdraw(true);
+        let expected_remainder = text_box.draw(&mut expected).unwrap();
+
+        let mut buffer = [LineRecord::default(); 16];
+        let layout = TextLayout::new(text_box, &mut buffer[..buffer_size]);
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let remainder = layout.draw(&mut display).unwrap();
+
+        display.assert_eq(&expected);
+        assert_eq!(expected_remainder, remainder);
+    }
+
+    #[test]
+    fn layout_matches_text_box() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .alignment(Justified)
+            .vertical_alignment(BottomAligned)
+            .height_mode(Exact(FullRowsOnly))
+            .paragraph_spacing(2)
+            .hanging_indent(6)
+            .build();
+
+        let text_box = TextBox::with_textbox_style(
+            "Lorem ipsum dolor sit amet, conse\u{ad}ctetur\nadipiscing elit\rsed",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 8, 5)),
+            character_style,
+            style,
+        );
+
+        for buffer_size in 0..16 {
+            assert_same_output(text_box.clone(), buffer_size);
+        }
+    }
+
+    #[test]
+    fn text_height_and_lines() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let text = "word word word\nword";
+        let text_box = TextBox::new(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 5)),
+            character_style,
+        );
+
+        let mut buffer = [LineRecord::default(); 4];
+        let layout = TextLayout::new(text_box.clone(), &mut buffer);
+
+        assert!(layout.is_complete());
+        assert_eq!(
+            text_box
+                .style
+                .measure_text_height(&character_style, text, 60),
+            layout.text_height()
+        );
+
+        let ranges = layout
+            .lines()
+            .iter()
+            .map(|line| (line.y(), &text[line.byte_range()]))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(0, "word word "), (9, "word\n"), (18, "word")]);
+
+        assert_eq!(layout.line_at(Point::new(3, 4)), Some(0));
+        assert_eq!(layout.line_at(Point::new(3, 9)), Some(1));
+        assert_eq!(layout.line_at(Point::new(3, 26)), Some(2));
+        assert_eq!(layout.line_at(Point::new(3, 27)), None);
+        assert_eq!(layout.line_at(Point::new(3, -1)), None);
+    }
+
+    #[test]
+    fn small_buffer_measures_the_rest() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let text_box = TextBox::new(
+            "word word word\nword",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 5)),
+            character_style,
+        );
+
+        let mut buffer = [LineRecord::default(); 1];
+        let layout = TextLayout::new(text_box, &mut buffer);
+
+        assert!(!layout.is_complete());
+        assert_eq!(1, layout.lines().len());
+        assert_eq!(27, layout.text_height());
+        assert_eq!(layout.line_at(Point::new(3, 8)), Some(0));
+        assert_eq!(layout.line_at(Point::new(3, 9)), None);
+    }
+
+    #[test]
+    fn extend_matches_new() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .alignment(Justified)
+            .paragraph_spacing(2)
+            .first_line_indent(6)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 8, 5));
+
+        let text =
+            "Lorem ipsum dolor sit amet, conse\u{ad}ctetur\n\nadipiscing\relit, sed do eiusmod";
+
+        for buffer_size in &[2, 16] {
+            let mut expected_buffer = [LineRecord::default(); 16];
+            let expected = TextLayout::new(
+                TextBox::with_textbox_style(text, bounds, character_style, style),
+                &mut expected_buffer[..*buffer_size],
+            )
+            .state();
+
+            for (split, _) in text.char_indices() {
+                let mut buffer = [LineRecord::default(); 16];
+                let buffer = &mut buffer[..*buffer_size];
+
+                let prefix =
+                    TextBox::with_textbox_style(&text[..split], bounds, character_style, style);
+                let state = TextLayout::new(prefix, buffer).state();
+
+                let text_box = TextBox::with_textbox_style(text, bounds, character_style, style);
+                let layout = TextLayout::extend(text_box, buffer, state);
+
+                assert_eq!(expected, layout.state(), "split at {}", split);
+                assert_eq!(&expected_buffer[..expected.line_count], layout.lines());
+            }
+        }
+
+        // A state of a longer text causes the text to be measured from the beginning.
+        let mut buffer = [LineRecord::default(); 16];
+        let text_box = TextBox::with_textbox_style(text, bounds, character_style, style);
+        let state = TextLayout::new(text_box, &mut buffer).state();
+
+        let short = TextBox::with_textbox_style("Lorem", bounds, character_style, style);
+        let extended = TextLayout::extend(short.clone(), &mut buffer, state).state();
+
+        let mut buffer = [LineRecord::default(); 16];
+        assert_eq!(TextLayout::new(short, &mut buffer).state(), extended);
+    }
+
+    fn assert_changes_drawn(
+        previous_text: &str,
+        text: &str,
+        buffer_size: usize,
+    ) -> MockDisplay<Rgb888> {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(Rgb888::WHITE)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .background_color(Rgb::new(0, 0, 255))
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 6, 4));
+
+        let mut expected = MockDisplay::new();
+        expected.set_allow_overdraw(true);
+        let mut buffer = [LineRecord::default(); 8];
+        let text_box = TextBox::with_textbox_style(text, bounds, character_style, style);
+        let layout = TextLayout::new(text_box, &mut buffer[..buffer_size]);
+        layout.draw(&mut expected).unwrap();
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut previous_buffer = [LineRecord::default(); 8];
+        let text_box = TextBox::with_textbox_style(previous_text, bounds, character_style, style);
+        let previous = TextLayout::new(text_box, &mut previous_buffer[..buffer_size]);
+        previous.draw(&mut display).unwrap();
+
+        layout.draw_changes(&previous, &mut display).unwrap();
+        display.assert_eq(&expected);
+
+        // Return the pixels that were drawn by `draw_changes`.
+        let mut changes = MockDisplay::new();
+        changes.set_allow_overdraw(true);
+        layout.draw_changes(&previous, &mut changes).unwrap();
+        changes
+    }
+
+    #[test]
+    fn draw_changed_lines() {
+        let changes = assert_changes_drawn("first\nline\nthird", "first\nword\nthird", 8);
+
+        let changed_rows = (0..36)
+            .filter(|y| (0..36).any(|x| changes.get_pixel(Point::new(x, *y)).is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(changed_rows, (9..18).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn draw_changes_clears_vacated_lines() {
+        assert_changes_drawn("first\nsecond\nthird", "first", 8);
+        assert_changes_drawn("first", "zeroth\nfirst", 8);
+        assert_changes_drawn("a b c d e f g", "a b", 2);
+        assert_changes_drawn("a b", "a b c d e f g", 2);
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn draw_changes_detects_style_changes() {
+        let changes = assert_changes_drawn(
+            "\x1b[31mfirst\nsecond\nthird",
+            "\x1b[32mfirst\nsecond\nthird",
+            8,
+        );
+
+        // Every line is redrawn because the style of every line is changed.
+        assert!(changes.get_pixel(Point::new(0, 20)).is_some());
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn draw_changes_detects_animation_changes() {
+        use crate::animation::{Animation, Reveal};
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(Rgb888::WHITE)
+            .build();
+        let style = TextBoxStyleBuilder::new()
+            .background_color(Rgb::new(0, 0, 255))
+            .blink_interval(2)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 6, 2));
+        let text = "\x1b[6mfirst\x1b[25m\nsecond";
+
+        let draw_changes = |previous: Animation, animation: Animation| {
+            let mut previous_buffer = [LineRecord::default(); 2];
+            let mut text_box = TextBox::with_textbox_style(text, bounds, character_style, style);
+            text_box.animation = previous;
+            let previous = TextLayout::new(text_box.clone(), &mut previous_buffer);
+
+            let mut buffer = [LineRecord::default(); 2];
+            text_box.animation = animation;
+            let layout = TextLayout::new(text_box, &mut buffer);
+
+            let mut expected = MockDisplay::new();
+            expected.set_allow_overdraw(true);
+            layout.draw(&mut expected).unwrap();
+
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            previous.draw(&mut display).unwrap();
+            layout.draw_changes(&previous, &mut display).unwrap();
+            display.assert_eq(&expected);
+
+            let mut changes = MockDisplay::new();
+            changes.set_allow_overdraw(true);
+            layout.draw_changes(&previous, &mut changes).unwrap();
+            changes
+        };
+
+        // Ticks in the same blink interval look the same.
+        let changes = draw_changes(Animation::new().tick(0), Animation::new().tick(1));
+        assert!(changes.affected_area().is_zero_sized());
+
+        // The blink phase changed.
+        let changes = draw_changes(Animation::new().tick(1), Animation::new().tick(2));
+        assert!(!changes.affected_area().is_zero_sized());
+
+        // More of the text is revealed.
+        let changes = draw_changes(
+            Animation::new().reveal(Reveal::Characters(3)),
+            Animation::new().reveal(Reveal::Characters(8)),
+        );
+        assert!(changes.get_pixel(Point::new(0, 12)).is_some());
+    }
+
+    #[cfg(feature = "ansi")]
+    fn hyperlinks(text: &str) -> Vec<(&str, Rectangle)> {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let text_box = TextBox::new(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 4)),
+            character_style,
+        );
+
+        let mut buffer = [LineRecord::default(); 4];
+        let layout = TextLayout::new(text_box, &mut buffer);
+
+        let mut links = Vec::new();
+        layout.hyperlinks(|uri, area| links.push((uri, area)));
+
+        links
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn hyperlinks_are_reported_per_line() {
+        let links = hyperlinks(
+            "Press \x1b]8;;ok\x1b\\OK\x1b]8;;\x1b\\ or \x1b]8;;more\x1b\\read more\x1b]8;;\x1b\\",
+        );
+
+        assert_eq!(
+            links,
+            [
+                ("ok", Rectangle::new(Point::new(36, 0), Size::new(12, 9))),
+                ("more", Rectangle::new(Point::new(18, 9), Size::new(24, 9))),
+                ("more", Rectangle::new(Point::new(0, 18), Size::new(24, 9))),
+            ]
+        );
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn hyperlink_at() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let text_box = TextBox::new(
+            "a \x1b]8;;x\x1b\\link\x1b]8;;\x1b\\",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 1)),
+            character_style,
+        );
+
+        let mut buffer = [LineRecord::default(); 1];
+        let layout = TextLayout::new(text_box, &mut buffer);
+
+        assert_eq!(layout.hyperlink_at(Point::new(3, 3)), None);
+        assert_eq!(layout.hyperlink_at(Point::new(12, 3)), Some("x"));
+        assert_eq!(layout.hyperlink_at(Point::new(35, 8)), Some("x"));
+        assert_eq!(layout.hyperlink_at(Point::new(36, 3)), None);
+    }
+
+    #[cfg(feature = "ansi")]
+    #[test]
+    fn hyperlinks_are_not_drawn() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 10, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new("a \x1b]8;;x\x07link\x1b]8;;\x07", bounds, character_style)
+            .draw(&mut display)
+            .unwrap();
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("a link", bounds, character_style)
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
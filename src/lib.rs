@@ -85,6 +85,7 @@
 //! ## Cargo features
 //!
 //! * `ansi`: enables ANSI sequence support. This feature is enabled by default.
+//! * `highlight`: enables syntax highlighting of code snippets.
 //!
 //! [embedded-graphics]: https://github.com/embedded-graphics/embedded-graphics/
 //! [the embedded-graphics simulator]: https://github.com/embedded-graphics/embedded-graphics/tree/master/simulator
@@ -106,19 +107,27 @@
 #![allow(clippy::needless_doctest_main)]
 
 pub mod alignment;
+pub mod animation;
+#[cfg(feature = "highlight")]
+pub mod highlight;
+pub mod layout;
 mod parser;
+pub mod renderer;
 pub mod rendering;
 pub mod style;
+pub mod table;
 
 mod utils;
 
 use crate::{
     alignment::HorizontalTextAlignment,
+    animation::{Animation, ScrollTransition},
     prelude::{Exact, HeightMode, LeftAligned, TopAligned, VerticalTextAlignment},
-    style::{vertical_overdraw::FullRowsOnly, TextBoxStyle},
+    renderer::Spans,
+    style::{vertical_overdraw::FullRowsOnly, Padding, Rotation, TextBoxStyle},
 };
 use embedded_graphics::{
-    geometry::{Dimensions, Point},
+    geometry::{Dimensions, Point, Size},
     primitives::Rectangle,
     text::renderer::{CharacterStyle, TextRenderer},
     transform::Transform,
@@ -132,10 +141,13 @@ pub mod prelude {
     #[doc(no_inline)]
     pub use crate::{
         alignment::*,
+        layout::{LayoutState, LineRecord, TextLayout},
         style::{
-            height_mode::{Exact, FitToText, HeightMode, ShrinkToText},
-            TabSize, TextBoxStyle, TextBoxStyleBuilder,
+            height_mode::{Exact, FitToText, FitToTextSize, HeightMode, ShrinkToText},
+            Padding, Rotation, TabAlignment, TabSize, TabStop, TextBoxStyle, TextBoxStyleBuilder,
+            WritingMode,
         },
+        table::Table,
         TextBox,
     };
 
@@ -170,6 +182,12 @@ pub struct TextBox<'a, S, A, V, H> {
 
     /// The style of the [`TextBox`].
     pub style: TextBoxStyle<A, V, H>,
+
+    /// The current frame of animated text.
+    pub animation: Animation,
+
+    /// Spans that change the character style of parts of the text.
+    pub spans: Option<&'a dyn Spans<S>>,
 }
 
 impl<'a, S> TextBox<'a, S, LeftAligned, TopAligned, Exact<FullRowsOnly>>
@@ -199,12 +217,43 @@ where
         bounds: Rectangle,
         character_style: S,
         textbox_style: TextBoxStyle<A, V, H>,
+    ) -> Self {
+        Self::with_optional_spans(text, bounds, character_style, textbox_style, None)
+    }
+
+    /// Creates a new `TextBox` instance whose text is drawn using the given [`Spans`].
+    ///
+    /// The spans look up the style of the text using byte offsets in `text`. Vertical text ignores
+    /// the spans. See [`Annotated`] for an example.
+    ///
+    /// [`Spans`]: renderer/trait.Spans.html
+    /// [`Annotated`]: renderer/struct.Annotated.html
+    #[inline]
+    #[must_use]
+    pub fn with_spans(
+        text: &'a str,
+        bounds: Rectangle,
+        character_style: S,
+        textbox_style: TextBoxStyle<A, V, H>,
+        spans: &'a dyn Spans<S>,
+    ) -> Self {
+        Self::with_optional_spans(text, bounds, character_style, textbox_style, Some(spans))
+    }
+
+    fn with_optional_spans(
+        text: &'a str,
+        bounds: Rectangle,
+        character_style: S,
+        textbox_style: TextBoxStyle<A, V, H>,
+        spans: Option<&'a dyn Spans<S>>,
     ) -> Self {
         let mut styled = TextBox {
             text,
             bounds,
             character_style,
             style: textbox_style,
+            animation: Animation::new(),
+            spans,
         };
 
         H::apply(&mut styled);
@@ -276,6 +325,43 @@ impl<S, A, V, H> Dimensions for TextBox<'_, S, A, V, H> {
     }
 }
 
+impl<S, A, V, H> TextBox<'_, S, A, V, H> {
+    /// Returns the bounding box of the `TextBox` in the coordinate system of the text.
+    ///
+    /// If the text is rotated, the returned rectangle has its top left corner at the origin and
+    /// its size is the rotated size of the bounding box.
+    #[inline]
+    #[must_use]
+    pub(crate) fn logical_bounds(&self) -> Rectangle {
+        match self.style.rotation {
+            Rotation::None => self.bounds,
+            rotation => Rectangle::new(Point::zero(), rotation.rotate_size(self.bounds.size)),
+        }
+    }
+
+    /// Sets the size of the `TextBox` in the coordinate system of the text.
+    #[inline]
+    pub(crate) fn set_logical_size(&mut self, size: Size) {
+        self.bounds.size = self.style.rotation.rotate_size(size);
+    }
+
+    /// Returns the area of the text box that is available for the text.
+    ///
+    /// This is the bounding box of the `TextBox`, without the border and the padding.
+    #[inline]
+    #[must_use]
+    pub(crate) fn text_area(&self) -> Rectangle {
+        let border = Padding::all(self.style.border_width);
+        let effect = self.style.text_effect.margins();
+
+        effect.apply(
+            self.style
+                .padding
+                .apply(border.apply(self.logical_bounds())),
+        )
+    }
+}
+
 impl<S, A, V, H> TextBox<'_, S, A, V, H>
 where
     S: TextRenderer,
@@ -291,22 +377,95 @@ where
     ///
     /// This method allows you to set a maximum height. The [`StyledTextBox`] will take up at most
     /// `max_height` pixel vertical space.
+    ///
+    /// The border and the vertical padding are included in the resulting height.
     #[inline]
     pub fn fit_height_limited(&mut self, max_height: u32) -> &mut Self {
-        // Measure text given the width of the textbox
+        // Measure text given the width of the text area
         let text_height = self
             .style
-            .measure_text_height(
+            .measure_spans_size(
                 &self.character_style,
+                self.spans,
                 self.text,
-                self.bounding_box().size.width,
+                self.text_area().size.width,
             )
+            .height
+            .saturating_add(self.style.padding.vertical())
+            .saturating_add(self.style.text_effect.margins().vertical())
+            .saturating_add(self.style.border_width.saturating_mul(2))
             .min(max_height)
             .min(i32::max_value() as u32);
 
         // Apply height
-        self.bounds.size.height = text_height;
+        let width = self.logical_bounds().size.width;
+        self.set_logical_size(Size::new(width, text_height));
+
+        self
+    }
+
+    /// Sets the size of the [`TextBox`] to the size of the text.
+    ///
+    /// The current width of the [`TextBox`] is used as the maximum width, so the width can only
+    /// shrink. The border and the padding are included in the resulting size.
+    #[inline]
+    pub fn fit_size(&mut self) -> &mut Self {
+        let frame = self.style.border_width.saturating_mul(2);
+        let effect = self.style.text_effect.margins();
+        let text_size = self.style.measure_spans_size(
+            &self.character_style,
+            self.spans,
+            self.text,
+            self.text_area().size.width,
+        );
+
+        let width = text_size
+            .width
+            .saturating_add(self.style.padding.horizontal())
+            .saturating_add(effect.horizontal())
+            .saturating_add(frame)
+            .min(self.logical_bounds().size.width);
+        let height = text_size
+            .height
+            .saturating_add(self.style.padding.vertical())
+            .saturating_add(effect.vertical())
+            .saturating_add(frame)
+            .min(i32::MAX as u32);
+
+        self.set_logical_size(Size::new(width, height));
 
         self
     }
 }
+
+impl<S, A, V, H> TextBox<'_, S, A, V, H>
+where
+    S: TextRenderer,
+    A: HorizontalTextAlignment,
+    V: VerticalTextAlignment,
+    H: HeightMode,
+{
+    /// Returns a transition that scrolls from the position of `previous_text` to the position of
+    /// the current text.
+    ///
+    /// The transition starts at the scroll offset that draws the lines where they were drawn for
+    /// `previous_text`, and ends at zero. It takes `duration` ticks.
+    ///
+    /// See [`ScrollTransition`] for an example.
+    ///
+    /// [`ScrollTransition`]: animation/struct.ScrollTransition.html
+    #[inline]
+    #[must_use]
+    pub fn scroll_transition(&self, previous_text: &str, duration: u32) -> ScrollTransition {
+        let text_area = self.text_area();
+        let offset = |text| {
+            let text_height =
+                self.style
+                    .measure_text_height(&self.character_style, text, text_area.size.width);
+
+            V::vertical_offset(text_height, text_area.size.height)
+        };
+
+        ScrollTransition::new(offset(previous_text) - offset(self.text), 0, duration)
+    }
+}
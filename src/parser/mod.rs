@@ -44,6 +44,13 @@ pub enum Token<'a> {
     /// An ANSI escape sequence
     #[cfg(feature = "ansi")]
     EscapeSequence(AnsiSequence),
+
+    /// An OSC 8 hyperlink escape sequence with the URI of the link.
+    ///
+    /// The link ends at the next hyperlink sequence. An empty URI ends the link without starting
+    /// a new one.
+    #[cfg(feature = "ansi")]
+    Hyperlink(&'a str),
 }
 
 /// Text parser. Turns a string into a stream of [`Token`] objects.
@@ -52,6 +59,9 @@ pub enum Token<'a> {
 #[derive(Clone, Debug)]
 pub struct Parser<'a> {
     inner: Chars<'a>,
+
+    /// Length of the whole text, used to calculate the offset of the parser.
+    len: usize,
 }
 
 pub(crate) const SPEC_CHAR_NBSP: char = '\u{a0}';
@@ -66,6 +76,35 @@ fn is_word_char(c: char) -> bool {
         && ![SPEC_CHAR_ZWSP, SPEC_CHAR_SHY, SPEC_CHAR_ESCAPE].contains(&c)
 }
 
+/// Parses an OSC 8 hyperlink sequence at the start of `text`.
+///
+/// The sequence has the format `ESC ] 8 ; params ; URI ST`, where the string terminator `ST` is
+/// either `ESC \` or `BEL`. Returns the URI and the rest of the text.
+#[cfg(feature = "ansi")]
+fn parse_hyperlink(text: &str) -> Option<(&str, &str)> {
+    const PREFIX: &str = "\x1b]8;";
+
+    if !text.starts_with(PREFIX) {
+        return None;
+    }
+
+    // Skip the parameters.
+    let text = &text[PREFIX.len()..];
+    let text = &text[text.find(';')? + 1..];
+
+    let end = text.find(&['\x07', SPEC_CHAR_ESCAPE][..])?;
+    let (uri, rest) = text.split_at(end);
+    let terminator_len = if rest.starts_with('\x07') {
+        1
+    } else if rest.starts_with("\x1b\\") {
+        2
+    } else {
+        return None;
+    };
+
+    Some((uri, &rest[terminator_len..]))
+}
+
 fn is_space_char(c: char) -> bool {
     // zero-width space breaks whitespace sequences - this works as long as
     // space handling is symmetrical (i.e. starting == ending behaviour)
@@ -79,9 +118,31 @@ impl<'a> Parser<'a> {
     pub fn parse(text: &'a str) -> Self {
         Self {
             inner: text.chars(),
+            len: text.len(),
         }
     }
 
+    /// Create a new parser object that starts at byte `offset` of the given text.
+    ///
+    /// The offsets returned by [`offset`] are relative to the start of `text`.
+    ///
+    /// [`offset`]: #method.offset
+    #[inline]
+    #[must_use]
+    pub fn parse_at(text: &'a str, offset: usize) -> Self {
+        Self {
+            inner: text[offset..].chars(),
+            len: text.len(),
+        }
+    }
+
+    /// Returns the byte offset of the next token in the text.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.len - self.inner.as_str().len()
+    }
+
     /// Returns true if there are no tokens to process.
     #[inline]
     #[must_use]
@@ -136,13 +197,20 @@ impl<'a> Iterator for Parser<'a> {
                     SPEC_CHAR_ZWSP => Some(Token::Break(None)),
                     SPEC_CHAR_SHY => Some(Token::Break(Some("-"))),
                     #[cfg(feature = "ansi")]
-                    SPEC_CHAR_ESCAPE => ansi_parser::parse_escape(string).map_or(
-                        Some(Token::EscapeSequence(AnsiSequence::Escape)),
-                        |(string, output)| {
-                            self.inner = string.chars();
-                            Some(Token::EscapeSequence(output))
-                        },
-                    ),
+                    SPEC_CHAR_ESCAPE => {
+                        if let Some((uri, rest)) = parse_hyperlink(string) {
+                            self.inner = rest.chars();
+                            return Some(Token::Hyperlink(uri));
+                        }
+
+                        ansi_parser::parse_escape(string).map_or(
+                            Some(Token::EscapeSequence(AnsiSequence::Escape)),
+                            |(string, output)| {
+                                self.inner = string.chars();
+                                Some(Token::EscapeSequence(output))
+                            },
+                        )
+                    }
 
                     // count consecutive whitespace
                     _ => {
@@ -231,6 +299,20 @@ mod test {
         assert_tokens("  \u{200B} ", vec![Token::Whitespace(3)]);
     }
 
+    #[test]
+    fn offsets_are_relative_to_the_whole_text() {
+        let text = "ab c\u{00AD}d";
+        let mut parser = Parser::parse_at(text, 3);
+
+        assert_eq!(parser.offset(), 3);
+        assert_eq!(parser.next(), Some(Token::Word("c")));
+        assert_eq!(parser.offset(), 4);
+        assert_eq!(parser.next(), Some(Token::Break(Some("-"))));
+        assert_eq!(parser.offset(), 6);
+        assert_eq!(parser.next(), Some(Token::Word("d")));
+        assert_eq!(parser.offset(), text.len());
+    }
+
     #[test]
     fn parse_multibyte_last() {
         assert_tokens("test😅", vec![Token::Word("test😅")]);
@@ -330,4 +412,36 @@ mod ansi_parser_tests {
             ],
         );
     }
+
+    #[test]
+    fn hyperlinks() {
+        assert_tokens(
+            "see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\.",
+            vec![
+                Token::Word("see"),
+                Token::Whitespace(1),
+                Token::Hyperlink("https://example.com"),
+                Token::Word("docs"),
+                Token::Hyperlink(""),
+                Token::Word("."),
+            ],
+        );
+
+        // parameters are ignored, BEL is accepted as string terminator
+        assert_tokens(
+            "\x1b]8;id=1;help\x07?",
+            vec![Token::Hyperlink("help"), Token::Word("?")],
+        );
+    }
+
+    #[test]
+    fn unterminated_hyperlink_is_not_parsed() {
+        assert_tokens(
+            "\x1b]8;;help",
+            vec![
+                Token::EscapeSequence(AnsiSequence::Escape),
+                Token::Word("]8;;help"),
+            ],
+        );
+    }
 }
@@ -0,0 +1,446 @@
+//! Styled spans of text.
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter,
+    ops::Range,
+};
+
+use embedded_graphics::text::{renderer::CharacterStyle, DecorationColor};
+
+/// Style changes applied to a span of text.
+///
+/// Properties that are set to `None` are inherited from the character style of the text.
+#[derive(Clone, PartialEq)]
+pub struct SpanStyle<S>
+where
+    S: CharacterStyle,
+{
+    /// Replaces the character style of the text, for example to use a different font.
+    pub character_style: Option<S>,
+
+    /// The text color.
+    pub text_color: Option<Option<S::Color>>,
+
+    /// The background color.
+    pub background_color: Option<Option<S::Color>>,
+
+    /// The underline color.
+    pub underline_color: Option<DecorationColor<S::Color>>,
+
+    /// The strikethrough color.
+    pub strikethrough_color: Option<DecorationColor<S::Color>>,
+}
+
+impl<S> SpanStyle<S>
+where
+    S: CharacterStyle,
+{
+    /// Creates a new `SpanStyle` that doesn't change anything.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            character_style: None,
+            text_color: None,
+            background_color: None,
+            underline_color: None,
+            strikethrough_color: None,
+        }
+    }
+
+    /// Replaces the character style of the text.
+    #[inline]
+    #[must_use]
+    pub fn character_style(mut self, character_style: S) -> Self {
+        self.character_style = Some(character_style);
+        self
+    }
+
+    /// Sets the text color.
+    #[inline]
+    #[must_use]
+    pub fn text_color(mut self, text_color: S::Color) -> Self {
+        self.text_color = Some(Some(text_color));
+        self
+    }
+
+    /// Sets the background color.
+    #[inline]
+    #[must_use]
+    pub fn background_color(mut self, background_color: S::Color) -> Self {
+        self.background_color = Some(Some(background_color));
+        self
+    }
+
+    /// Underlines the text using the text color.
+    #[inline]
+    #[must_use]
+    pub fn underline(mut self) -> Self {
+        self.underline_color = Some(DecorationColor::TextColor);
+        self
+    }
+
+    /// Strikes through the text using the text color.
+    #[inline]
+    #[must_use]
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough_color = Some(DecorationColor::TextColor);
+        self
+    }
+
+    /// Applies the changes to a character style.
+    pub(crate) fn apply(&self, style: &mut S)
+    where
+        S: Clone,
+    {
+        if let Some(character_style) = &self.character_style {
+            *style = character_style.clone();
+        }
+        if let Some(text_color) = self.text_color {
+            style.set_text_color(text_color);
+        }
+        if let Some(background_color) = self.background_color {
+            style.set_background_color(background_color);
+        }
+        if let Some(underline_color) = self.underline_color {
+            style.set_underline_color(underline_color);
+        }
+        if let Some(strikethrough_color) = self.strikethrough_color {
+            style.set_strikethrough_color(strikethrough_color);
+        }
+    }
+}
+
+impl<S> fmt::Debug for SpanStyle<S>
+where
+    S: CharacterStyle + fmt::Debug,
+    S::Color: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpanStyle")
+            .field("character_style", &self.character_style)
+            .field("text_color", &self.text_color)
+            .field("background_color", &self.background_color)
+            .field("underline_color", &self.underline_color)
+            .field("strikethrough_color", &self.strikethrough_color)
+            .finish()
+    }
+}
+
+impl<S> Default for SpanStyle<S>
+where
+    S: CharacterStyle,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Style changes for byte ranges of the text of a [`TextBox`].
+///
+/// The text box asks for the character style of every string and whitespace that it measures and
+/// draws, using the byte offset of the string in its text. Strings are split where the style
+/// changes, so spans may start and end inside words without affecting how the text is wrapped.
+///
+/// Spans are compared and hashed by their address, because they usually refer to state that
+/// can't be compared, like a tokenizer.
+///
+/// [`TextBox`]: ../struct.TextBox.html
+pub trait Spans<S> {
+    /// Returns the character style at byte `offset` of the text, and the offset where the style
+    /// may change next.
+    ///
+    /// `style` is the character style of the text box, including the changes made by escape
+    /// sequences. `None` means that the text at `offset` is drawn using `style`. If the style
+    /// doesn't change after `offset`, the returned offset is `usize::MAX`.
+    fn style_at(&self, style: &S, offset: usize) -> (Option<S>, usize);
+}
+
+impl<S> PartialEq for dyn Spans<S> + '_ {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.address() == other.address()
+    }
+}
+
+impl<S> Eq for dyn Spans<S> + '_ {}
+
+impl<S> Hash for dyn Spans<S> + '_ {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address().hash(state);
+    }
+}
+
+impl<S> fmt::Debug for dyn Spans<S> + '_ {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Spans").field(&self.address()).finish()
+    }
+}
+
+impl<S> dyn Spans<S> + '_ {
+    /// Returns the address of the spans, without the vtable.
+    fn address(&self) -> usize {
+        self as *const Self as *const u8 as usize
+    }
+}
+
+/// Annotated spans of a text.
+///
+/// Each annotation is a byte range of the text and the style changes applied to it. If spans
+/// overlap, the later span takes precedence.
+///
+/// The annotations belong to a specific string, which must be the text of the text box that uses
+/// them. The hyphen that is inserted at a soft hyphen uses the style of the soft hyphen, and
+/// whitespace uses the style of its first character.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyle},
+///     pixelcolor::Rgb888,
+///     prelude::*,
+/// };
+/// use embedded_text::{
+///     prelude::*,
+///     renderer::{Annotated, SpanStyle},
+///     style::TextBoxStyle,
+/// };
+///
+/// let text = "Search results are highlighted.";
+///
+/// // Highlight every match of "light".
+/// let highlight = SpanStyle::new()
+///     .text_color(Rgb888::BLACK)
+///     .background_color(Rgb888::YELLOW);
+/// let spans = [(25..30, highlight)];
+/// let annotated = Annotated::new(&spans);
+///
+/// let text_box = TextBox::with_spans(
+///     text,
+///     Rectangle::new(Point::zero(), Size::new(120, 18)),
+///     MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+///     TextBoxStyle::default(),
+///     &annotated,
+/// );
+///
+/// let mut display: MockDisplay<Rgb888> = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Annotated<'a, S>
+where
+    S: CharacterStyle,
+{
+    /// The byte ranges of the text and their styles.
+    pub spans: &'a [(Range<usize>, SpanStyle<S>)],
+}
+
+impl<'a, S> Annotated<'a, S>
+where
+    S: CharacterStyle,
+{
+    /// Creates new `Annotated` spans.
+    #[inline]
+    #[must_use]
+    pub fn new(spans: &'a [(Range<usize>, SpanStyle<S>)]) -> Self {
+        Self { spans }
+    }
+}
+
+impl<S> fmt::Debug for Annotated<'_, S>
+where
+    S: CharacterStyle + fmt::Debug,
+    S::Color: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Annotated")
+            .field("spans", &self.spans)
+            .finish()
+    }
+}
+
+impl<S> Spans<S> for Annotated<'_, S>
+where
+    S: CharacterStyle + Clone,
+{
+    #[inline]
+    fn style_at(&self, style: &S, offset: usize) -> (Option<S>, usize) {
+        let mut changed = None;
+        for (_, span) in self
+            .spans
+            .iter()
+            .filter(|(range, _)| range.contains(&offset))
+        {
+            span.apply(changed.get_or_insert_with(|| style.clone()));
+        }
+
+        let next_boundary = self
+            .spans
+            .iter()
+            .flat_map(|(range, _)| iter::once(range.start).chain(iter::once(range.end)))
+            .filter(|boundary| *boundary > offset)
+            .min()
+            .unwrap_or(usize::MAX);
+
+        (changed, next_boundary)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{
+            ascii::{FONT_10X20, FONT_6X9},
+            MonoTextStyle,
+        },
+        pixelcolor::Rgb888,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        renderer::{styled_width, Annotated, SpanStyle},
+        style::TextBoxStyle,
+        utils::test::size_for,
+        TextBox,
+    };
+
+    fn draw_annotated(
+        text: &str,
+        size: Size,
+        spans: &[(
+            core::ops::Range<usize>,
+            SpanStyle<MonoTextStyle<'static, Rgb888>>,
+        )],
+    ) -> MockDisplay<Rgb888> {
+        let annotated = Annotated::new(spans);
+
+        let mut display = MockDisplay::new();
+        TextBox::with_spans(
+            text,
+            Rectangle::new(Point::zero(), size),
+            MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+            TextBoxStyle::default(),
+            &annotated,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn spans_change_the_style() {
+        let spans = [(1..2, SpanStyle::new().text_color(Rgb888::RED))];
+        let display = draw_annotated("abc", size_for(&FONT_6X9, 3, 1), &spans);
+
+        display.assert_pattern(&[
+            "                  ",
+            "       R          ",
+            "       R          ",
+            "  WWW  RRR    WWW ",
+            " W  W  R  R  W    ",
+            " W  W  R  R  W    ",
+            "  WWW  RRR    WWW ",
+        ]);
+    }
+
+    #[test]
+    fn spans_apply_to_copies_of_the_text() {
+        let spans = [(1..2, SpanStyle::new().text_color(Rgb888::RED))];
+        let copy = String::from("abc");
+
+        let display = draw_annotated(&copy, size_for(&FONT_6X9, 3, 1), &spans);
+        let expected = draw_annotated("abc", size_for(&FONT_6X9, 3, 1), &spans);
+
+        display.assert_eq(&expected);
+        assert_eq!(display.get_pixel(Point::new(7, 1)), Some(Rgb888::RED));
+    }
+
+    #[test]
+    fn spans_are_measured_with_their_style() {
+        let text = "abc de";
+        let spans = [(
+            1..2,
+            SpanStyle::new().character_style(MonoTextStyle::new(&FONT_10X20, Rgb888::WHITE)),
+        )];
+        let annotated = Annotated::new(&spans);
+        let base = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+
+        assert_eq!(
+            styled_width(Some(&annotated), &base, &text[0..3], 0),
+            6 + 10 + 6
+        );
+        assert_eq!(styled_width(Some(&annotated), &base, &text[2..3], 2), 6);
+        assert_eq!(styled_width(Some(&annotated), &base, &text[4..], 4), 2 * 6);
+    }
+
+    #[test]
+    fn spans_follow_wrapped_words() {
+        // The second word is wrapped, the third one is broken because it's longer than the line.
+        let text = "ab cd efghij";
+        let spans = [
+            (3..5, SpanStyle::new().text_color(Rgb888::RED)),
+            (10..12, SpanStyle::new().text_color(Rgb888::GREEN)),
+        ];
+        let display = draw_annotated(text, size_for(&FONT_6X9, 4, 4), &spans);
+
+        // The lines are "ab", "cd", "efgh" and "ij".
+        let line_color = |line: i32| {
+            let mut colors = (0..24)
+                .flat_map(|x| (0..9).map(move |y| Point::new(x, line * 9 + y)))
+                .filter_map(|point| display.get_pixel(point))
+                .collect::<Vec<_>>();
+            colors.dedup();
+
+            colors
+        };
+        assert_eq!(line_color(0), [Rgb888::WHITE]);
+        assert_eq!(line_color(1), [Rgb888::RED]);
+        assert_eq!(line_color(2), [Rgb888::WHITE]);
+        assert_eq!(line_color(3), [Rgb888::GREEN]);
+    }
+
+    #[test]
+    fn later_spans_take_precedence() {
+        let spans = [
+            (0..2, SpanStyle::new().text_color(Rgb888::RED)),
+            (1..2, SpanStyle::new().text_color(Rgb888::GREEN)),
+        ];
+        let display = draw_annotated("ab", size_for(&FONT_6X9, 2, 1), &spans);
+
+        display.assert_pattern(&[
+            "            ",
+            "       G    ",
+            "       G    ",
+            "  RRR  GGG  ",
+            " R  R  G  G ",
+            " R  R  G  G ",
+            "  RRR  GGG  ",
+        ]);
+    }
+
+    #[test]
+    fn whitespace_uses_the_style_of_the_span() {
+        let spans = [(0..3, SpanStyle::new().background_color(Rgb888::RED))];
+        let display = draw_annotated("a b c", size_for(&FONT_6X9, 5, 1), &spans);
+
+        // The space between the highlighted words has the background color of the span, the
+        // space after the span doesn't.
+        for x in 0..18 {
+            assert_eq!(display.get_pixel(Point::new(x, 0)), Some(Rgb888::RED));
+        }
+        for x in 18..30 {
+            assert_eq!(display.get_pixel(Point::new(x, 0)), None);
+        }
+    }
+}
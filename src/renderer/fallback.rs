@@ -0,0 +1,385 @@
+//! Font fallback.
+use crate::renderer::{baseline_y, envelope};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Point,
+    mono_font::MonoTextStyle,
+    pixelcolor::PixelColor,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+/// Character styles that can tell which characters they are able to draw.
+pub trait GlyphCoverage {
+    /// Returns `true` if the character style has a glyph for `c`.
+    fn contains_glyph(&self, c: char) -> bool;
+}
+
+impl<C> GlyphCoverage for MonoTextStyle<'_, C>
+where
+    C: PixelColor,
+{
+    /// Returns `true` if the font has a glyph for `c`.
+    ///
+    /// Fonts draw missing characters using a replacement glyph. A character is considered missing
+    /// if the font maps it to the same glyph as `U+FFFF`, which is not a valid character and so it
+    /// is never included in a font. The fonts of `embedded-graphics` use `?` as the replacement
+    /// glyph, so `?` is always reported as present.
+    #[inline]
+    fn contains_glyph(&self, c: char) -> bool {
+        let mapping = self.font.glyph_mapping;
+
+        c == '?' || mapping.index(c) != mapping.index('\u{FFFF}')
+    }
+}
+
+/// A character style that draws characters missing from a font using a fallback character style.
+///
+/// Each character is drawn with the primary character style if it contains the character, and
+/// with the fallback character style otherwise. Text is measured using the same choice, so
+/// wrapping and alignment use the width of the glyphs that are actually drawn. Characters that
+/// neither style contains are drawn with the primary character style.
+///
+/// The fallback can be another `FontFallback`, which makes it possible to chain any number of
+/// fonts. Every font in the chain is drawn on the baseline of the primary character style.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii, iso_8859_2, MonoTextStyleBuilder},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{prelude::*, renderer::FontFallback};
+///
+/// let character_style = FontFallback::new(
+///     MonoTextStyleBuilder::new()
+///         .font(&ascii::FONT_6X9)
+///         .text_color(BinaryColor::On)
+///         .build(),
+///     MonoTextStyleBuilder::new()
+///         .font(&iso_8859_2::FONT_6X9)
+///         .text_color(BinaryColor::On)
+///         .build(),
+/// );
+///
+/// let text_box = TextBox::new(
+///     "Dzień dobry",
+///     Rectangle::new(Point::zero(), Size::new(66, 9)),
+///     character_style,
+/// );
+///
+/// let mut display: MockDisplay<BinaryColor> = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FontFallback<P, F> {
+    /// The character style used to draw the characters it contains.
+    pub primary: P,
+
+    /// The character style used to draw the characters missing from the primary style.
+    pub fallback: F,
+}
+
+impl<P, F> FontFallback<P, F> {
+    /// Creates a new `FontFallback` character style.
+    #[inline]
+    #[must_use]
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+impl<P, F> FontFallback<P, F>
+where
+    P: GlyphCoverage,
+    F: GlyphCoverage,
+{
+    /// Returns `true` if `c` is drawn with the fallback character style.
+    fn uses_fallback(&self, c: char) -> bool {
+        !self.primary.contains_glyph(c) && self.fallback.contains_glyph(c)
+    }
+
+    /// Splits the text into pieces that are drawn with the same character style.
+    fn runs<'t>(&self, text: &'t str) -> Runs<'t, '_, P, F> {
+        Runs { text, style: self }
+    }
+}
+
+impl<P, F> GlyphCoverage for FontFallback<P, F>
+where
+    P: GlyphCoverage,
+    F: GlyphCoverage,
+{
+    #[inline]
+    fn contains_glyph(&self, c: char) -> bool {
+        self.primary.contains_glyph(c) || self.fallback.contains_glyph(c)
+    }
+}
+
+/// A piece of text drawn with the primary or the fallback character style.
+enum Run<'t> {
+    Primary(&'t str),
+    Fallback(&'t str),
+}
+
+struct Runs<'t, 's, P, F> {
+    text: &'t str,
+    style: &'s FontFallback<P, F>,
+}
+
+impl<'t, P, F> Iterator for Runs<'t, '_, P, F>
+where
+    P: GlyphCoverage,
+    F: GlyphCoverage,
+{
+    type Item = Run<'t>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.text.chars().next()?;
+
+        let style = self.style;
+        let fallback = style.uses_fallback(first);
+        let end = self
+            .text
+            .char_indices()
+            .find(|(_, c)| style.uses_fallback(*c) != fallback)
+            .map_or(self.text.len(), |(index, _)| index);
+
+        let (text, rest) = self.text.split_at(end);
+        self.text = rest;
+
+        Some(if fallback {
+            Run::Fallback(text)
+        } else {
+            Run::Primary(text)
+        })
+    }
+}
+
+impl<P, F> TextRenderer for FontFallback<P, F>
+where
+    P: TextRenderer + GlyphCoverage,
+    F: TextRenderer<Color = P::Color> + GlyphCoverage,
+{
+    type Color = P::Color;
+
+    #[inline]
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let baseline_y = baseline_y(&self.primary, position, baseline);
+
+        let mut position = position;
+        for run in self.runs(text) {
+            position = match run {
+                Run::Primary(text) => self.primary.draw_string(text, position, baseline, target)?,
+                Run::Fallback(text) => {
+                    let next = self.fallback.draw_string(
+                        text,
+                        Point::new(position.x, baseline_y),
+                        Baseline::Alphabetic,
+                        target,
+                    )?;
+
+                    Point::new(next.x, position.y)
+                }
+            };
+        }
+
+        Ok(position)
+    }
+
+    #[inline]
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.primary
+            .draw_whitespace(width, position, baseline, target)
+    }
+
+    #[inline]
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let baseline_y = baseline_y(&self.primary, position, baseline);
+
+        let mut metrics = self.primary.measure_string("", position, baseline);
+        for run in self.runs(text) {
+            let run_metrics = match run {
+                Run::Primary(text) => {
+                    self.primary
+                        .measure_string(text, metrics.next_position, baseline)
+                }
+                Run::Fallback(text) => self.fallback.measure_string(
+                    text,
+                    Point::new(metrics.next_position.x, baseline_y),
+                    Baseline::Alphabetic,
+                ),
+            };
+
+            metrics.next_position.x = run_metrics.next_position.x;
+            metrics.bounding_box = envelope(&metrics.bounding_box, &run_metrics.bounding_box);
+        }
+
+        metrics
+    }
+
+    #[inline]
+    fn line_height(&self) -> u32 {
+        self.primary.line_height()
+    }
+}
+
+impl<P, F> CharacterStyle for FontFallback<P, F>
+where
+    P: CharacterStyle,
+    F: CharacterStyle<Color = P::Color>,
+{
+    type Color = P::Color;
+
+    #[inline]
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.primary.set_text_color(text_color);
+        self.fallback.set_text_color(text_color);
+    }
+
+    #[inline]
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.primary.set_background_color(background_color);
+        self.fallback.set_background_color(background_color);
+    }
+
+    #[inline]
+    fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+        self.primary.set_underline_color(underline_color);
+        self.fallback.set_underline_color(underline_color);
+    }
+
+    #[inline]
+    fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+        self.primary.set_strikethrough_color(strikethrough_color);
+        self.fallback.set_strikethrough_color(strikethrough_color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii, iso_8859_1, MonoFont, MonoTextStyle, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        renderer::{FontFallback, GlyphCoverage},
+        utils::{str_width, test::size_for},
+        TextBox,
+    };
+
+    fn style(font: &'static MonoFont) -> MonoTextStyle<'static, BinaryColor> {
+        MonoTextStyleBuilder::new()
+            .font(font)
+            .text_color(BinaryColor::On)
+            .build()
+    }
+
+    #[test]
+    fn mono_font_coverage() {
+        let style = style(&ascii::FONT_6X9);
+
+        assert!(style.contains_glyph('a'));
+        assert!(style.contains_glyph(' '));
+        assert!(!style.contains_glyph('é'));
+        assert!(!style.contains_glyph('Ж'));
+    }
+
+    #[test]
+    fn replacement_glyph_is_covered() {
+        assert!(style(&ascii::FONT_6X9).contains_glyph('?'));
+        assert!(style(&iso_8859_1::FONT_6X9).contains_glyph('?'));
+        assert!(style(&DIGITS_6X9).contains_glyph('?'));
+    }
+
+    /// Returns the glyph index of `c` in an ASCII font that only contains digits.
+    fn digits(c: char) -> usize {
+        let c = if c.is_ascii_digit() { c } else { '?' };
+
+        c as usize - ' ' as usize
+    }
+
+    const DIGITS_6X9: MonoFont = MonoFont {
+        glyph_mapping: &(digits as fn(char) -> usize),
+        ..ascii::FONT_6X9
+    };
+
+    #[test]
+    fn characters_are_measured_with_the_font_that_draws_them() {
+        let character_style = FontFallback::new(style(&DIGITS_6X9), style(&ascii::FONT_10X20));
+
+        assert_eq!(str_width(&character_style, "12"), 2 * 6);
+        assert_eq!(str_width(&character_style, "1a"), 6 + 10);
+        assert_eq!(str_width(&character_style, "a12b"), 10 + 2 * 6 + 10);
+
+        // Characters missing from every font are drawn with the primary font.
+        assert_eq!(
+            str_width(&character_style, "é"),
+            str_width(&style(&DIGITS_6X9), "é")
+        );
+        assert_eq!(str_width(&character_style, "?"), 6);
+    }
+
+    #[test]
+    fn fallbacks_can_be_chained() {
+        let character_style = FontFallback::new(
+            style(&DIGITS_6X9),
+            FontFallback::new(style(&ascii::FONT_10X20), style(&iso_8859_1::FONT_9X15)),
+        );
+
+        assert!(character_style.contains_glyph('1'));
+        assert!(character_style.contains_glyph('a'));
+        assert!(character_style.contains_glyph('é'));
+        assert!(!character_style.contains_glyph('Ж'));
+
+        assert_eq!(str_width(&character_style, "1a"), 6 + 10);
+    }
+
+    #[test]
+    fn missing_characters_are_drawn_with_the_fallback_font() {
+        let bounds = Rectangle::new(Point::zero(), size_for(&ascii::FONT_6X9, 3, 1));
+
+        let mut display = MockDisplay::new();
+        TextBox::new(
+            "aéb",
+            bounds,
+            FontFallback::new(style(&ascii::FONT_6X9), style(&iso_8859_1::FONT_6X9)),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        let mut expected = MockDisplay::new();
+        TextBox::new("aéb", bounds, style(&iso_8859_1::FONT_6X9))
+            .draw(&mut expected)
+            .unwrap();
+
+        display.assert_eq(&expected);
+    }
+}
@@ -0,0 +1,579 @@
+//! Anti-aliased fonts.
+use crate::renderer::GlyphCoverage;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Point, Size},
+    pixelcolor::{PixelColor, Rgb888, RgbColor},
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline,
+    },
+    Pixel,
+};
+
+/// A pre-rasterized glyph of a [`GrayscaleFont`].
+///
+/// [`GrayscaleFont`]: struct.GrayscaleFont.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GrayscaleGlyph<'a> {
+    /// The character drawn by the glyph.
+    pub character: char,
+
+    /// Coverage of the pixels of the glyph, row by row.
+    ///
+    /// `0` is a transparent pixel, `255` is a pixel that is completely covered by the glyph.
+    pub coverage: &'a [u8],
+
+    /// Width of the glyph, in pixels.
+    pub width: u32,
+
+    /// Position of the top left pixel of the glyph, relative to the start of the glyph on the
+    /// baseline.
+    pub offset: Point,
+
+    /// Horizontal distance between the start of this glyph and the start of the next one.
+    pub advance: u32,
+}
+
+impl<'a> GrayscaleGlyph<'a> {
+    /// Creates a new glyph.
+    #[inline]
+    #[must_use]
+    pub const fn new(
+        character: char,
+        coverage: &'a [u8],
+        width: u32,
+        offset: Point,
+        advance: u32,
+    ) -> Self {
+        Self {
+            character,
+            coverage,
+            width,
+            offset,
+            advance,
+        }
+    }
+}
+
+/// A proportional font of pre-rasterized, anti-aliased glyphs.
+///
+/// Glyphs are usually generated from a BDF, TrueType or other font file by a build script or an
+/// external tool. The font should contain a glyph for the space character, which is used to
+/// measure the width of spaces.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GrayscaleFont<'a> {
+    /// The glyphs of the font.
+    ///
+    /// The glyphs must be sorted by their character, because they are looked up using a binary
+    /// search.
+    pub glyphs: &'a [GrayscaleGlyph<'a>],
+
+    /// Distance between the top of a line and the baseline, in pixels.
+    pub ascent: u32,
+
+    /// Distance between the baseline and the bottom of a line, in pixels.
+    pub descent: u32,
+
+    /// The character drawn in place of characters that are missing from the font.
+    ///
+    /// Missing characters are skipped if the replacement character is missing, too.
+    pub replacement_character: char,
+}
+
+impl<'a> GrayscaleFont<'a> {
+    /// Returns the glyph of `c`, or the glyph of the replacement character if `c` is missing.
+    fn glyph(&self, c: char) -> Option<&'a GrayscaleGlyph<'a>> {
+        self.find(c)
+            .or_else(|| self.find(self.replacement_character))
+    }
+
+    /// Returns the glyph of `c`.
+    fn find(&self, c: char) -> Option<&'a GrayscaleGlyph<'a>> {
+        let glyphs = self.glyphs;
+        glyphs
+            .binary_search_by_key(&c, |glyph| glyph.character)
+            .ok()
+            .map(|index| &glyphs[index])
+    }
+
+    /// Returns the height of a line.
+    fn height(&self) -> u32 {
+        self.ascent + self.descent
+    }
+}
+
+/// Character style of a [`GrayscaleFont`].
+///
+/// The edges of the glyphs are blended with the background color. If no background color is set,
+/// the background of the display can't be blended with, so only the pixels that are covered by
+/// at least half are drawn, using the text color. The background color can also be changed by
+/// ANSI sequences, which makes it possible to draw anti-aliased text on colored backgrounds.
+///
+/// Underlines and strikethrough lines are not supported.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{mock_display::MockDisplay, pixelcolor::Rgb888, prelude::*};
+/// use embedded_text::{
+///     prelude::*,
+///     renderer::{GrayscaleFont, GrayscaleGlyph, GrayscaleTextStyle},
+/// };
+///
+/// // A font with a space and a single, 2 pixel wide glyph.
+/// const GLYPHS: &[GrayscaleGlyph] = &[
+///     GrayscaleGlyph::new(' ', &[], 0, Point::zero(), 2),
+///     GrayscaleGlyph::new('l', &[255, 64, 255, 64, 255, 128], 2, Point::new(0, -3), 3),
+/// ];
+/// const FONT: GrayscaleFont = GrayscaleFont {
+///     glyphs: GLYPHS,
+///     ascent: 3,
+///     descent: 1,
+///     replacement_character: ' ',
+/// };
+///
+/// let mut character_style = GrayscaleTextStyle::new(&FONT, Rgb888::WHITE);
+/// character_style.background_color = Some(Rgb888::BLUE);
+///
+/// let text_box = TextBox::new(
+///     "ll l",
+///     Rectangle::new(Point::zero(), Size::new(20, 4)),
+///     character_style,
+/// );
+///
+/// // The glyphs are drawn over the background.
+/// let mut display: MockDisplay<Rgb888> = MockDisplay::new();
+/// display.set_allow_overdraw(true);
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GrayscaleTextStyle<'a, C> {
+    /// The font.
+    pub font: &'a GrayscaleFont<'a>,
+
+    /// The text color.
+    pub text_color: Option<C>,
+
+    /// The background color, which the edges of the glyphs are blended with.
+    pub background_color: Option<C>,
+}
+
+impl<'a, C> GrayscaleTextStyle<'a, C> {
+    /// Creates a new `GrayscaleTextStyle` with a transparent background.
+    ///
+    /// In debug builds, this function panics if the glyphs of the font aren't sorted by their
+    /// character.
+    #[inline]
+    #[must_use]
+    pub fn new(font: &'a GrayscaleFont<'a>, text_color: C) -> Self {
+        debug_assert!(
+            font.glyphs
+                .windows(2)
+                .all(|pair| pair[0].character < pair[1].character),
+            "the glyphs of the font must be sorted by their character"
+        );
+
+        Self {
+            font,
+            text_color: Some(text_color),
+            background_color: None,
+        }
+    }
+
+    /// Returns the distance between the top of the line and the position of text drawn at
+    /// `baseline`.
+    fn baseline_offset(&self, baseline: Baseline) -> i32 {
+        let bottom = self.font.height().saturating_sub(1) as i32;
+
+        match baseline {
+            Baseline::Top => 0,
+            Baseline::Bottom => bottom,
+            Baseline::Middle => bottom / 2,
+            Baseline::Alphabetic => self.font.ascent as i32,
+        }
+    }
+}
+
+impl<C> GrayscaleTextStyle<'_, C>
+where
+    C: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
+    /// Returns the color of a pixel with the given coverage, or `None` if it isn't drawn.
+    fn pixel_color(&self, coverage: u8) -> Option<C> {
+        let text_color = self.text_color?;
+
+        // Uncovered pixels keep the background, which is drawn before the glyph.
+        match self.background_color {
+            _ if coverage == 0 => None,
+            Some(background_color) => Some(blend(background_color, text_color, coverage)),
+            None if coverage >= 128 => Some(text_color),
+            None => None,
+        }
+    }
+
+    /// Fills the area of the line between `x` and `x + width` with the background color.
+    fn fill_background<D>(
+        &self,
+        x: i32,
+        top: i32,
+        width: u32,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        match self.background_color {
+            Some(color) if width > 0 => target.fill_solid(
+                &Rectangle::new(Point::new(x, top), Size::new(width, self.font.height())),
+                color,
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Blends two colors, using the coverage of the foreground color.
+fn blend<C>(background: C, foreground: C, coverage: u8) -> C
+where
+    C: Into<Rgb888> + From<Rgb888>,
+{
+    let background = background.into();
+    let foreground = foreground.into();
+
+    let alpha = u32::from(coverage);
+    let channel = |background: u8, foreground: u8| {
+        let mixed = u32::from(background) * (255 - alpha) + u32::from(foreground) * alpha;
+
+        ((mixed + 127) / 255) as u8
+    };
+
+    Rgb888::new(
+        channel(background.r(), foreground.r()),
+        channel(background.g(), foreground.g()),
+        channel(background.b(), foreground.b()),
+    )
+    .into()
+}
+
+impl<C> TextRenderer for GrayscaleTextStyle<'_, C>
+where
+    C: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
+    type Color = C;
+
+    #[inline]
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let top = position.y - self.baseline_offset(baseline);
+        let baseline_y = top + self.font.ascent as i32;
+
+        // The background is filled first, so it doesn't cover the parts of the glyphs that extend
+        // beyond their advance.
+        let width = text
+            .chars()
+            .filter_map(|c| self.font.glyph(c))
+            .map(|glyph| glyph.advance)
+            .sum();
+        self.fill_background(position.x, top, width, target)?;
+
+        let mut x = position.x;
+        for glyph in text.chars().filter_map(|c| self.font.glyph(c)) {
+            let origin = Point::new(x, baseline_y) + glyph.offset;
+            let width = glyph.width.max(1) as usize;
+            let pixels = glyph
+                .coverage
+                .iter()
+                .enumerate()
+                .filter_map(|(index, coverage)| {
+                    let point = origin + Point::new((index % width) as i32, (index / width) as i32);
+
+                    self.pixel_color(*coverage).map(|color| Pixel(point, color))
+                });
+            target.draw_iter(pixels)?;
+
+            x += glyph.advance as i32;
+        }
+
+        Ok(Point::new(x, position.y))
+    }
+
+    #[inline]
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let top = position.y - self.baseline_offset(baseline);
+        self.fill_background(position.x, top, width, target)?;
+
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    #[inline]
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let width = text
+            .chars()
+            .filter_map(|c| self.font.glyph(c))
+            .map(|glyph| glyph.advance)
+            .sum::<u32>();
+
+        let top = position.y - self.baseline_offset(baseline);
+
+        TextMetrics {
+            bounding_box: Rectangle::new(
+                Point::new(position.x, top),
+                Size::new(width, self.font.height()),
+            ),
+            next_position: position + Point::new(width as i32, 0),
+        }
+    }
+
+    #[inline]
+    fn line_height(&self) -> u32 {
+        self.font.height()
+    }
+}
+
+impl<C> CharacterStyle for GrayscaleTextStyle<'_, C>
+where
+    C: PixelColor,
+{
+    type Color = C;
+
+    #[inline]
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.text_color = text_color;
+    }
+
+    #[inline]
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.background_color = background_color;
+    }
+}
+
+impl<C> GlyphCoverage for GrayscaleTextStyle<'_, C> {
+    #[inline]
+    fn contains_glyph(&self, c: char) -> bool {
+        self.font.find(c).is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::{BinaryColor, Rgb888},
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::{CenterAligned, HorizontalTextAlignment, Justified, LeftAligned},
+        renderer::{GrayscaleFont, GrayscaleGlyph, GrayscaleTextStyle},
+        style::{color::Rgb, TextBoxStyle},
+        utils::str_width,
+        TextBox,
+    };
+
+    const GLYPHS: &[GrayscaleGlyph] = &[
+        GrayscaleGlyph::new(' ', &[], 0, Point::zero(), 2),
+        GrayscaleGlyph::new(',', &[127], 1, Point::new(0, -1), 2),
+        GrayscaleGlyph::new('.', &[128], 1, Point::new(0, -1), 2),
+        GrayscaleGlyph::new('?', &[255], 1, Point::new(0, -3), 2),
+        GrayscaleGlyph::new('i', &[255, 255, 255], 1, Point::new(0, -3), 2),
+        GrayscaleGlyph::new('m', &[255; 9], 3, Point::new(0, -3), 4),
+    ];
+
+    const FONT: GrayscaleFont = GrayscaleFont {
+        glyphs: GLYPHS,
+        ascent: 3,
+        descent: 1,
+        replacement_character: '?',
+    };
+
+    fn draw<A: HorizontalTextAlignment, C>(
+        text: &str,
+        width: u32,
+        alignment: A,
+        character_style: GrayscaleTextStyle<'static, C>,
+    ) -> MockDisplay<C>
+    where
+        C: PixelColor + Into<Rgb888> + From<Rgb888> + From<Rgb>,
+    {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), Size::new(width, 8)),
+            character_style,
+            TextBoxStyle::with_alignment(alignment),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn glyphs_are_measured_by_their_advance() {
+        let character_style = GrayscaleTextStyle::new(&FONT, BinaryColor::On);
+
+        assert_eq!(str_width(&character_style, "mi"), 6);
+        assert_eq!(str_width(&character_style, "i m"), 8);
+
+        // Missing characters are measured using the replacement character.
+        assert_eq!(str_width(&character_style, "x"), 2);
+    }
+
+    #[test]
+    fn proportional_text_is_wrapped_by_width() {
+        let display = draw(
+            "mm ii m",
+            12,
+            LeftAligned,
+            GrayscaleTextStyle::new(&FONT, BinaryColor::On),
+        );
+
+        display.assert_pattern(&[
+            "### ###     ",
+            "### ###     ",
+            "### ###     ",
+            "            ",
+            "# #   ###   ",
+            "# #   ###   ",
+            "# #   ###   ",
+        ]);
+    }
+
+    #[test]
+    fn proportional_text_is_centered() {
+        let display = draw(
+            "i mm",
+            14,
+            CenterAligned,
+            GrayscaleTextStyle::new(&FONT, BinaryColor::On),
+        );
+
+        display.assert_pattern(&[" #   ### ###  ", " #   ### ###  ", " #   ### ###  "]);
+    }
+
+    #[test]
+    fn proportional_text_is_justified() {
+        let display = draw(
+            "i i mm",
+            12,
+            Justified,
+            GrayscaleTextStyle::new(&FONT, BinaryColor::On),
+        );
+
+        display.assert_pattern(&[
+            "#         # ",
+            "#         # ",
+            "#         # ",
+            "            ",
+            "### ###     ",
+            "### ###     ",
+            "### ###     ",
+        ]);
+    }
+
+    #[test]
+    fn half_covered_pixels_are_drawn_without_background() {
+        let display = draw(
+            ".,",
+            4,
+            LeftAligned,
+            GrayscaleTextStyle::new(&FONT, BinaryColor::On),
+        );
+
+        display.assert_pattern(&["    ", "    ", "#   "]);
+    }
+
+    #[test]
+    fn edges_are_blended_with_the_background() {
+        let mut character_style = GrayscaleTextStyle::new(&FONT, Rgb888::WHITE);
+        character_style.background_color = Some(Rgb888::BLACK);
+
+        let display = draw(".", 2, LeftAligned, character_style);
+
+        assert_eq!(
+            display.get_pixel(Point::new(0, 2)),
+            Some(Rgb888::new(128, 128, 128))
+        );
+        assert_eq!(display.get_pixel(Point::new(1, 2)), Some(Rgb888::BLACK));
+        assert_eq!(display.get_pixel(Point::new(0, 3)), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    fn background_does_not_cover_overhanging_glyphs() {
+        // "f" extends into the next glyph, which doesn't cover that pixel.
+        const GLYPHS: &[GrayscaleGlyph] = &[
+            GrayscaleGlyph::new(' ', &[], 0, Point::zero(), 2),
+            GrayscaleGlyph::new('f', &[255, 255], 2, Point::new(0, -1), 1),
+            GrayscaleGlyph::new('v', &[255], 1, Point::new(0, -2), 2),
+        ];
+        const FONT: GrayscaleFont = GrayscaleFont {
+            glyphs: GLYPHS,
+            ascent: 3,
+            descent: 1,
+            replacement_character: ' ',
+        };
+
+        let mut character_style = GrayscaleTextStyle::new(&FONT, Rgb888::WHITE);
+        character_style.background_color = Some(Rgb888::BLACK);
+
+        let display = draw("fv", 3, LeftAligned, character_style);
+
+        assert_eq!(display.get_pixel(Point::new(1, 2)), Some(Rgb888::WHITE));
+        assert_eq!(display.get_pixel(Point::new(1, 1)), Some(Rgb888::WHITE));
+        assert_eq!(display.get_pixel(Point::new(2, 2)), Some(Rgb888::BLACK));
+    }
+
+    #[test]
+    #[cfg(feature = "ansi")]
+    fn edges_are_blended_with_the_ansi_background() {
+        let display = draw(
+            "\x1b[48;2;0;0;255m.",
+            2,
+            LeftAligned,
+            GrayscaleTextStyle::new(&FONT, Rgb888::WHITE),
+        );
+
+        assert_eq!(
+            display.get_pixel(Point::new(0, 2)),
+            Some(Rgb888::new(128, 128, 255))
+        );
+        assert_eq!(display.get_pixel(Point::new(1, 2)), Some(Rgb888::BLUE));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "the glyphs of the font must be sorted by their character")]
+    fn unsorted_glyphs_are_rejected() {
+        const UNSORTED: GrayscaleFont = GrayscaleFont {
+            glyphs: &[
+                GrayscaleGlyph::new('i', &[255, 255, 255], 1, Point::new(0, -3), 2),
+                GrayscaleGlyph::new(' ', &[], 0, Point::zero(), 2),
+            ],
+            ..FONT
+        };
+
+        let _ = GrayscaleTextStyle::new(&UNSORTED, Rgb888::WHITE);
+    }
+}
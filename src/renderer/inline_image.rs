@@ -0,0 +1,354 @@
+//! Images embedded in text.
+use crate::renderer::{baseline_y, envelope};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Point,
+    image::{Image, ImageDrawable},
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline, DecorationColor,
+    },
+    Drawable,
+};
+
+/// A character style that draws images in place of placeholder characters.
+///
+/// Every placeholder character in the text is replaced by its image. Images are measured like
+/// characters, so they are wrapped together with the word they are part of. The bottom row of
+/// an image is placed on the baseline of the text, and lines that contain images taller than the
+/// font grow to fit them.
+///
+/// Placeholder characters should be characters that don't otherwise appear in the text, for
+/// example characters from the Unicode Private Use Area (`U+E000` to `U+F8FF`).
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     image::ImageRaw,
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{prelude::*, renderer::InlineImages};
+///
+/// const BATTERY: char = '\u{E000}';
+///
+/// #[rustfmt::skip]
+/// let battery = ImageRaw::<BinaryColor>::new(&[
+///     0b11111100,
+///     0b10000110,
+///     0b11111100,
+/// ], 7);
+///
+/// let images = [(BATTERY, battery)];
+/// let character_style = InlineImages::new(
+///     MonoTextStyleBuilder::new()
+///         .font(&FONT_6X9)
+///         .text_color(BinaryColor::On)
+///         .build(),
+///     &images,
+/// );
+///
+/// let text_box = TextBox::new(
+///     "Battery: \u{E000} 80%",
+///     Rectangle::new(Point::zero(), Size::new(96, 9)),
+///     character_style,
+/// );
+///
+/// let mut display: MockDisplay<BinaryColor> = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct InlineImages<'a, S, I> {
+    /// The character style used to draw the text.
+    pub character_style: S,
+
+    /// The placeholder characters and their images.
+    pub images: &'a [(char, I)],
+}
+
+impl<'a, S, I> InlineImages<'a, S, I> {
+    /// Creates a new `InlineImages` character style.
+    #[inline]
+    #[must_use]
+    pub fn new(character_style: S, images: &'a [(char, I)]) -> Self {
+        Self {
+            character_style,
+            images,
+        }
+    }
+
+    /// Returns the image of the given placeholder character.
+    fn image(&self, c: char) -> Option<&'a I> {
+        self.images
+            .iter()
+            .find(|(placeholder, _)| *placeholder == c)
+            .map(|(_, image)| image)
+    }
+
+    /// Splits the text into pieces of text and images.
+    fn runs<'t>(&self, text: &'t str) -> Runs<'t, 'a, '_, S, I> {
+        Runs { text, style: self }
+    }
+}
+
+impl<S: Clone, I> Clone for InlineImages<'_, S, I> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            character_style: self.character_style.clone(),
+            images: self.images,
+        }
+    }
+}
+
+/// A piece of text, or an image.
+enum Run<'t, 'a, I> {
+    Text(&'t str),
+    Image(&'a I),
+}
+
+struct Runs<'t, 'a, 's, S, I> {
+    text: &'t str,
+    style: &'s InlineImages<'a, S, I>,
+}
+
+impl<'t, 'a, S, I> Iterator for Runs<'t, 'a, '_, S, I> {
+    type Item = Run<'t, 'a, I>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.text.is_empty() {
+            return None;
+        }
+
+        let style = self.style;
+        let next_image = self
+            .text
+            .char_indices()
+            .find_map(|(index, c)| style.image(c).map(|image| (index, c, image)));
+
+        let (run, rest) = match next_image {
+            Some((0, c, image)) => (Run::Image(image), &self.text[c.len_utf8()..]),
+            Some((index, _, _)) => (Run::Text(&self.text[..index]), &self.text[index..]),
+            None => (Run::Text(self.text), ""),
+        };
+        self.text = rest;
+
+        Some(run)
+    }
+}
+
+impl<S, I> TextRenderer for InlineImages<'_, S, I>
+where
+    S: TextRenderer,
+    I: ImageDrawable<Color = S::Color>,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let baseline_y = baseline_y(&self.character_style, position, baseline);
+
+        let mut position = position;
+        for run in self.runs(text) {
+            position = match run {
+                Run::Text(text) => self
+                    .character_style
+                    .draw_string(text, position, baseline, target)?,
+                Run::Image(image) => {
+                    let size = image.size();
+                    let top_left = Point::new(position.x, baseline_y + 1 - size.height as i32);
+                    Image::new(image, top_left).draw(target)?;
+
+                    position + size.x_axis()
+                }
+            };
+        }
+
+        Ok(position)
+    }
+
+    #[inline]
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.character_style
+            .draw_whitespace(width, position, baseline, target)
+    }
+
+    #[inline]
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let baseline_y = baseline_y(&self.character_style, position, baseline);
+
+        let mut metrics = self.character_style.measure_string("", position, baseline);
+        for run in self.runs(text) {
+            let bounding_box = match run {
+                Run::Text(text) => {
+                    let run_metrics =
+                        self.character_style
+                            .measure_string(text, metrics.next_position, baseline);
+                    metrics.next_position = run_metrics.next_position;
+
+                    run_metrics.bounding_box
+                }
+                Run::Image(image) => {
+                    let size = image.size();
+                    let x = metrics.next_position.x;
+                    metrics.next_position += size.x_axis();
+
+                    Rectangle::new(Point::new(x, baseline_y + 1 - size.height as i32), size)
+                }
+            };
+
+            metrics.bounding_box = envelope(&metrics.bounding_box, &bounding_box);
+        }
+
+        metrics
+    }
+
+    #[inline]
+    fn line_height(&self) -> u32 {
+        self.character_style.line_height()
+    }
+}
+
+impl<S, I> CharacterStyle for InlineImages<'_, S, I>
+where
+    S: CharacterStyle,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.character_style.set_text_color(text_color);
+    }
+
+    #[inline]
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.character_style.set_background_color(background_color);
+    }
+
+    #[inline]
+    fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+        self.character_style.set_underline_color(underline_color);
+    }
+
+    #[inline]
+    fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+        self.character_style
+            .set_strikethrough_color(strikethrough_color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        image::ImageRaw,
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        renderer::InlineImages,
+        utils::{str_width, test::size_for},
+        TextBox,
+    };
+
+    const ICON: char = '\u{E000}';
+
+    #[rustfmt::skip]
+    const ICON_DATA: [u8; 4] = [
+        0b11100000,
+        0b10100000,
+        0b10100000,
+        0b11100000,
+    ];
+
+    fn character_style<'a>(
+        images: &'a [(char, ImageRaw<'static, BinaryColor>)],
+    ) -> InlineImages<'a, MonoTextStyle<'static, BinaryColor>, ImageRaw<'static, BinaryColor>> {
+        InlineImages::new(
+            MonoTextStyleBuilder::new()
+                .font(&FONT_6X9)
+                .text_color(BinaryColor::On)
+                .build(),
+            images,
+        )
+    }
+
+    #[test]
+    fn images_are_measured_as_characters() {
+        let images = [(ICON, ImageRaw::new(&ICON_DATA, 3))];
+        let character_style = character_style(&images);
+
+        assert_eq!(str_width(&character_style, "a\u{E000}b"), 6 + 3 + 6);
+        assert_eq!(str_width(&character_style, "\u{E000}\u{E000}"), 2 * 3);
+    }
+
+    #[test]
+    fn images_are_drawn_on_the_baseline() {
+        let images = [(ICON, ImageRaw::new(&ICON_DATA, 3))];
+
+        let mut display = MockDisplay::new();
+        TextBox::new(
+            "a\u{E000}a",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1)),
+            character_style(&images),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "               ",
+            "               ",
+            "               ",
+            "  ### ###  ### ",
+            " #  # #.# #  # ",
+            " #  # #.# #  # ",
+            "  ### ###  ### ",
+        ]);
+    }
+
+    #[test]
+    fn tall_images_increase_the_line_height() {
+        const DATA: [u8; 12] = [0b11000000; 12];
+        let images = [(ICON, ImageRaw::new(&DATA, 2))];
+
+        let mut display = MockDisplay::new();
+        TextBox::new(
+            "a\u{E000}\na",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 3)),
+            character_style(&images),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "      ##", "      ##", "      ##", "      ##", "      ##", "      ##", "      ##",
+            "      ##", "  ### ##", " #  # ##", " #  # ##", "  ### ##", "        ", "        ",
+            "        ", "        ", "        ", "  ###   ", " #  #   ", " #  #   ", "  ###   ",
+        ]);
+    }
+}
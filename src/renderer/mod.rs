@@ -0,0 +1,134 @@
+//! Text renderers and text renderer adapters.
+//!
+//! Most types in this module wrap a character style and change how parts of the text are measured
+//! and drawn. Because the text box measures every piece of text using the same character style
+//! that draws it, wrapping and alignment take the changes into account. [`GrayscaleTextStyle`]
+//! draws proportional, anti-aliased fonts.
+//!
+//! [`Spans`] change the character style of byte ranges of the text. They are passed to the text
+//! box using [`TextBox::with_spans`].
+//!
+//! [`GrayscaleTextStyle`]: grayscale/struct.GrayscaleTextStyle.html
+//! [`Spans`]: annotated/trait.Spans.html
+//! [`TextBox::with_spans`]: ../struct.TextBox.html#method.with_spans
+use crate::utils::str_width;
+use embedded_graphics::{
+    geometry::{Point, Size},
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Baseline},
+};
+
+pub mod annotated;
+pub mod fallback;
+pub mod grayscale;
+pub mod inline_image;
+pub mod substitution;
+
+pub use annotated::{Annotated, SpanStyle, Spans};
+pub use fallback::{FontFallback, GlyphCoverage};
+pub use grayscale::{GrayscaleFont, GrayscaleGlyph, GrayscaleTextStyle};
+pub use inline_image::InlineImages;
+pub use substitution::GlyphSubstitution;
+
+/// Returns the smallest rectangle that contains both rectangles.
+fn envelope(a: &Rectangle, b: &Rectangle) -> Rectangle {
+    let bottom_right = |r: &Rectangle| r.top_left + r.size;
+
+    let top_left = a.top_left.component_min(b.top_left);
+    let size = bottom_right(a).component_max(bottom_right(b)) - top_left;
+
+    Rectangle::new(top_left, Size::new(size.x as u32, size.y as u32))
+}
+
+/// Returns the vertical position of the alphabetic baseline of text drawn at `position`.
+fn baseline_y(renderer: &impl TextRenderer, position: Point, baseline: Baseline) -> i32 {
+    let top = |baseline| {
+        renderer
+            .measure_string("", position, baseline)
+            .bounding_box
+            .top_left
+            .y
+    };
+
+    position.y + top(baseline) - top(Baseline::Alphabetic)
+}
+
+/// Splits a string into pieces that have the same character style.
+///
+/// Each piece is returned with the character style that changes it, or `None` if it uses the
+/// base style.
+pub(crate) struct StyledPieces<'t, 's, S> {
+    text: &'t str,
+    offset: usize,
+    base: &'s S,
+    spans: Option<&'s dyn Spans<S>>,
+}
+
+impl<'t, 's, S> StyledPieces<'t, 's, S> {
+    /// Splits `text`, which starts at byte `offset` of the text of the text box.
+    pub(crate) fn new(
+        spans: Option<&'s dyn Spans<S>>,
+        base: &'s S,
+        text: &'t str,
+        offset: usize,
+    ) -> Self {
+        Self {
+            text,
+            offset,
+            base,
+            spans,
+        }
+    }
+}
+
+impl<'t, S> Iterator for StyledPieces<'t, '_, S> {
+    type Item = (&'t str, Option<S>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.text.is_empty() {
+            return None;
+        }
+
+        let (style, end) = style_at(self.spans, self.base, self.offset);
+
+        let mut len = end.saturating_sub(self.offset).min(self.text.len());
+        if len == 0 {
+            len = self.text.len();
+        }
+        while !self.text.is_char_boundary(len) {
+            len += 1;
+        }
+
+        let (piece, rest) = self.text.split_at(len);
+        self.text = rest;
+        self.offset += len;
+
+        Some((piece, style))
+    }
+}
+
+/// Returns the character style at byte `offset` of the text, and the offset where it may change.
+///
+/// The returned style is `None` if the text at `offset` uses the base style.
+pub(crate) fn style_at<S>(
+    spans: Option<&dyn Spans<S>>,
+    base: &S,
+    offset: usize,
+) -> (Option<S>, usize) {
+    spans.map_or((None, usize::MAX), |spans| spans.style_at(base, offset))
+}
+
+/// Returns the width of a string at byte `offset` of the text, in pixels.
+pub(crate) fn styled_width<S>(
+    spans: Option<&dyn Spans<S>>,
+    base: &S,
+    text: &str,
+    offset: usize,
+) -> u32
+where
+    S: TextRenderer,
+{
+    StyledPieces::new(spans, base, text, offset)
+        .map(|(piece, style)| str_width(style.as_ref().unwrap_or(base), piece))
+        .sum()
+}
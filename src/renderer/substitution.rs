@@ -0,0 +1,339 @@
+//! Glyph substitution.
+use crate::renderer::{baseline_y, envelope};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::Point,
+    text::{
+        renderer::{CharacterStyle, TextMetrics, TextRenderer},
+        Baseline, DecorationColor,
+    },
+};
+
+/// A character style that draws parts of the text using a secondary character style.
+///
+/// Each substitution maps a pattern, which is a character or a short code like `:wifi:`, to a
+/// replacement text. Patterns in the text are drawn as the replacement text, using the
+/// substitute character style, on the same baseline as the rest of the text. Substitutions are
+/// measured using the substitute style, too, so wrapping and alignment use their real width.
+///
+/// A typical use is drawing icons from a font that puts them into the Unicode Private Use Area.
+///
+/// If more patterns match at the same position, the first one in the list is used. Patterns that
+/// contain whitespace are never matched, and a pattern that is split by word wrapping is drawn
+/// as regular text.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{
+///         ascii::{FONT_6X9, FONT_9X15},
+///         MonoTextStyleBuilder,
+///     },
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{prelude::*, renderer::GlyphSubstitution};
+///
+/// // The icon font of a real application would map the short code to an icon glyph.
+/// let substitutions = [(":wifi:", "W")];
+/// let character_style = GlyphSubstitution::new(
+///     MonoTextStyleBuilder::new()
+///         .font(&FONT_6X9)
+///         .text_color(BinaryColor::On)
+///         .build(),
+///     MonoTextStyleBuilder::new()
+///         .font(&FONT_9X15)
+///         .text_color(BinaryColor::On)
+///         .build(),
+///     &substitutions,
+/// );
+///
+/// let text_box = TextBox::new(
+///     "Connected :wifi:",
+///     Rectangle::new(Point::zero(), Size::new(96, 15)),
+///     character_style,
+/// );
+///
+/// let mut display: MockDisplay<BinaryColor> = MockDisplay::new();
+/// text_box.draw(&mut display).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct GlyphSubstitution<'a, S, T> {
+    /// The character style used to draw the text.
+    pub character_style: S,
+
+    /// The character style used to draw the replacements.
+    pub substitute_style: T,
+
+    /// The patterns and their replacements.
+    pub substitutions: &'a [(&'a str, &'a str)],
+}
+
+impl<'a, S, T> GlyphSubstitution<'a, S, T> {
+    /// Creates a new `GlyphSubstitution` character style.
+    #[inline]
+    #[must_use]
+    pub fn new(
+        character_style: S,
+        substitute_style: T,
+        substitutions: &'a [(&'a str, &'a str)],
+    ) -> Self {
+        Self {
+            character_style,
+            substitute_style,
+            substitutions,
+        }
+    }
+
+    /// Splits the text into pieces of regular text and replacements.
+    fn runs<'t>(&self, text: &'t str) -> Runs<'t, 'a> {
+        Runs {
+            text,
+            substitutions: self.substitutions,
+        }
+    }
+}
+
+/// A piece of regular text, or the replacement of a pattern.
+enum Run<'t, 'a> {
+    Text(&'t str),
+    Replacement(&'a str),
+}
+
+struct Runs<'t, 'a> {
+    text: &'t str,
+    substitutions: &'a [(&'a str, &'a str)],
+}
+
+impl<'t, 'a> Runs<'t, 'a> {
+    /// Returns the substitution whose pattern is at the start of `text`.
+    fn substitution_at(&self, text: &str) -> Option<(&'a str, &'a str)> {
+        self.substitutions
+            .iter()
+            .copied()
+            .find(|(pattern, _)| !pattern.is_empty() && text.starts_with(pattern))
+    }
+}
+
+impl<'t, 'a> Iterator for Runs<'t, 'a> {
+    type Item = Run<'t, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.text.is_empty() {
+            return None;
+        }
+
+        let text = self.text;
+        let next_substitution = text.char_indices().find_map(|(index, _)| {
+            self.substitution_at(&text[index..])
+                .map(|substitution| (index, substitution))
+        });
+
+        let (run, rest) = match next_substitution {
+            Some((0, (pattern, replacement))) => {
+                (Run::Replacement(replacement), &text[pattern.len()..])
+            }
+            Some((index, _)) => (Run::Text(&text[..index]), &text[index..]),
+            None => (Run::Text(text), ""),
+        };
+        self.text = rest;
+
+        Some(run)
+    }
+}
+
+impl<S, T> TextRenderer for GlyphSubstitution<'_, S, T>
+where
+    S: TextRenderer,
+    T: TextRenderer<Color = S::Color>,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn draw_string<D>(
+        &self,
+        text: &str,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let baseline_y = baseline_y(&self.character_style, position, baseline);
+
+        let mut position = position;
+        for run in self.runs(text) {
+            position = match run {
+                Run::Text(text) => self
+                    .character_style
+                    .draw_string(text, position, baseline, target)?,
+                Run::Replacement(replacement) => {
+                    let next = self.substitute_style.draw_string(
+                        replacement,
+                        Point::new(position.x, baseline_y),
+                        Baseline::Alphabetic,
+                        target,
+                    )?;
+
+                    Point::new(next.x, position.y)
+                }
+            };
+        }
+
+        Ok(position)
+    }
+
+    #[inline]
+    fn draw_whitespace<D>(
+        &self,
+        width: u32,
+        position: Point,
+        baseline: Baseline,
+        target: &mut D,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.character_style
+            .draw_whitespace(width, position, baseline, target)
+    }
+
+    #[inline]
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let baseline_y = baseline_y(&self.character_style, position, baseline);
+
+        let mut metrics = self.character_style.measure_string("", position, baseline);
+        for run in self.runs(text) {
+            let run_metrics = match run {
+                Run::Text(text) => {
+                    self.character_style
+                        .measure_string(text, metrics.next_position, baseline)
+                }
+                Run::Replacement(replacement) => self.substitute_style.measure_string(
+                    replacement,
+                    Point::new(metrics.next_position.x, baseline_y),
+                    Baseline::Alphabetic,
+                ),
+            };
+
+            metrics.next_position.x = run_metrics.next_position.x;
+            metrics.bounding_box = envelope(&metrics.bounding_box, &run_metrics.bounding_box);
+        }
+
+        metrics
+    }
+
+    #[inline]
+    fn line_height(&self) -> u32 {
+        self.character_style.line_height()
+    }
+}
+
+impl<S, T> CharacterStyle for GlyphSubstitution<'_, S, T>
+where
+    S: CharacterStyle,
+    T: CharacterStyle<Color = S::Color>,
+{
+    type Color = S::Color;
+
+    #[inline]
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.character_style.set_text_color(text_color);
+        self.substitute_style.set_text_color(text_color);
+    }
+
+    #[inline]
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.character_style.set_background_color(background_color);
+        self.substitute_style.set_background_color(background_color);
+    }
+
+    #[inline]
+    fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+        self.character_style.set_underline_color(underline_color);
+        self.substitute_style.set_underline_color(underline_color);
+    }
+
+    #[inline]
+    fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+        self.character_style
+            .set_strikethrough_color(strikethrough_color);
+        self.substitute_style
+            .set_strikethrough_color(strikethrough_color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{
+            ascii::{FONT_10X20, FONT_6X9},
+            MonoFont, MonoTextStyle, MonoTextStyleBuilder,
+        },
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        renderer::GlyphSubstitution,
+        utils::{str_width, test::size_for},
+        TextBox,
+    };
+
+    fn style(font: &'static MonoFont) -> MonoTextStyle<'static, BinaryColor> {
+        MonoTextStyleBuilder::new()
+            .font(font)
+            .text_color(BinaryColor::On)
+            .build()
+    }
+
+    #[test]
+    fn substitutions_are_measured_using_the_substitute_style() {
+        let substitutions = [(":wifi:", "W"), ("\u{E000}", "BT")];
+        let character_style =
+            GlyphSubstitution::new(style(&FONT_6X9), style(&FONT_10X20), &substitutions);
+
+        assert_eq!(str_width(&character_style, ":wifi:"), 10);
+        assert_eq!(str_width(&character_style, "a:wifi:b"), 6 + 10 + 6);
+        assert_eq!(str_width(&character_style, "\u{E000}:wifi"), 2 * 10 + 5 * 6);
+    }
+
+    #[test]
+    fn first_matching_pattern_is_used() {
+        let substitutions = [("ab", "X"), ("a", "YY")];
+        let character_style =
+            GlyphSubstitution::new(style(&FONT_6X9), style(&FONT_10X20), &substitutions);
+
+        assert_eq!(str_width(&character_style, "abc"), 10 + 6);
+        assert_eq!(str_width(&character_style, "ac"), 2 * 10 + 6);
+    }
+
+    #[test]
+    fn draw_substitution() {
+        let substitutions = [("x", "o")];
+
+        let mut display = MockDisplay::new();
+        TextBox::new(
+            "axb",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 1)),
+            GlyphSubstitution::new(style(&FONT_6X9), style(&FONT_6X9), &substitutions),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "                  ",
+            "             #    ",
+            "             #    ",
+            "  ###   ##   ###  ",
+            " #  #  #  #  #  # ",
+            " #  #  #  #  #  # ",
+            "  ###   ##   ###  ",
+        ]);
+    }
+}
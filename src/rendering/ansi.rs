@@ -1,5 +1,17 @@
 //! ANSI escape sequence related types and functions.
-use crate::style::color::Rgb;
+use crate::style::{color::Rgb, BlinkMode, Decoration, TextBoxStyle};
+use core::convert::Infallible;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point, Size},
+    pixelcolor::PixelColor,
+    primitives::Rectangle,
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline, DecorationColor,
+    },
+    Pixel,
+};
 
 /// List of supported SGR (Select Graphics Rendition) sequences
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -10,6 +22,9 @@ pub enum Sgr {
     /// Draw a line under the text
     Underline,
 
+    /// Draw two lines under the text
+    DoubleUnderline,
+
     /// Cross out the text
     CrossedOut,
 
@@ -19,6 +34,15 @@ pub enum Sgr {
     /// Disable crossing out
     NotCrossedOut,
 
+    /// Blink slowly
+    SlowBlink,
+
+    /// Blink rapidly
+    RapidBlink,
+
+    /// Disable blinking
+    BlinkOff,
+
     /// Change the text color
     ChangeTextColor(Rgb),
 
@@ -30,6 +54,12 @@ pub enum Sgr {
 
     /// Reset the background color to transparent
     DefaultBackgroundColor,
+
+    /// Change the underline color
+    ChangeUnderlineColor(Rgb),
+
+    /// Reset the underline color to the text color
+    DefaultUnderlineColor,
 }
 
 fn try_parse_8b_color(v: &[u8]) -> Option<Rgb> {
@@ -114,23 +144,604 @@ pub fn try_parse_sgr(v: &[u8]) -> Option<Sgr> {
     match code {
         0 => Some(Sgr::Reset),
         4 => Some(Sgr::Underline),
+        5 => Some(Sgr::SlowBlink),
+        6 => Some(Sgr::RapidBlink),
         9 => Some(Sgr::CrossedOut),
+        21 => Some(Sgr::DoubleUnderline),
         24 => Some(Sgr::UnderlineOff),
+        25 => Some(Sgr::BlinkOff),
         29 => Some(Sgr::NotCrossedOut),
-        39 => Some(Sgr::DefaultTextColor),
-        49 => Some(Sgr::DefaultBackgroundColor),
         30..=37 => Some(Sgr::ChangeTextColor(standard_to_rgb(code - 30))),
         38 => {
             let color = try_parse_color(&v[1..])?;
             Some(Sgr::ChangeTextColor(color))
         }
-        90..=97 => Some(Sgr::ChangeTextColor(standard_to_rgb(code - 82))),
+        39 => Some(Sgr::DefaultTextColor),
         40..=47 => Some(Sgr::ChangeBackgroundColor(standard_to_rgb(code - 40))),
         48 => {
             let color = try_parse_color(&v[1..])?;
             Some(Sgr::ChangeBackgroundColor(color))
         }
+        49 => Some(Sgr::DefaultBackgroundColor),
+        58 => {
+            let color = try_parse_color(&v[1..])?;
+            Some(Sgr::ChangeUnderlineColor(color))
+        }
+        59 => Some(Sgr::DefaultUnderlineColor),
+        90..=97 => Some(Sgr::ChangeTextColor(standard_to_rgb(code - 82))),
         100..=107 => Some(Sgr::ChangeBackgroundColor(standard_to_rgb(code - 92))),
         _ => None,
     }
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Underline {
+    None,
+    Single,
+    Double,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Blink {
+    None,
+    Slow,
+    Rapid,
+}
+
+/// Text attributes set by SGR sequences that the character style can't handle by itself.
+///
+/// Lines that the font can draw are enabled on the character style. Double underlines and lines
+/// that are moved or thickened by the text box style are drawn by
+/// [`Attributes::draw_decorations`].
+///
+/// Blinking text is drawn using the style returned by [`Attributes::blink_style`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Attributes {
+    underline_style: Decoration,
+    strikethrough_style: Decoration,
+    blink_mode: BlinkMode,
+    blink_interval: u32,
+    tick: u32,
+
+    underline: Underline,
+    underline_color: Option<Rgb>,
+    strikethrough: bool,
+    blink: Blink,
+}
+
+impl Attributes {
+    pub fn new<A, V, H>(style: &TextBoxStyle<A, V, H>, tick: u32) -> Self {
+        Self {
+            underline_style: style.underline,
+            strikethrough_style: style.strikethrough,
+            blink_mode: style.blink_mode,
+            blink_interval: style.blink_interval,
+            tick,
+            underline: Underline::None,
+            underline_color: None,
+            strikethrough: false,
+            blink: Blink::None,
+        }
+    }
+
+    /// Applies an SGR sequence to the attributes and the character style.
+    pub fn apply<F>(&mut self, sgr: Sgr, renderer: &mut F)
+    where
+        F: CharacterStyle,
+        <F as CharacterStyle>::Color: From<Rgb>,
+    {
+        sgr.apply(renderer);
+
+        match sgr {
+            Sgr::Reset => {
+                self.underline = Underline::None;
+                self.underline_color = None;
+                self.strikethrough = false;
+                self.blink = Blink::None;
+            }
+            Sgr::Underline => self.underline = Underline::Single,
+            Sgr::DoubleUnderline => self.underline = Underline::Double,
+            Sgr::UnderlineOff => self.underline = Underline::None,
+            Sgr::ChangeUnderlineColor(color) => self.underline_color = Some(color),
+            Sgr::DefaultUnderlineColor => self.underline_color = None,
+            Sgr::CrossedOut => self.strikethrough = true,
+            Sgr::NotCrossedOut => self.strikethrough = false,
+
+            Sgr::SlowBlink => self.blink = Blink::Slow,
+            Sgr::RapidBlink => self.blink = Blink::Rapid,
+            Sgr::BlinkOff => self.blink = Blink::None,
+            Sgr::ChangeTextColor(_)
+            | Sgr::DefaultTextColor
+            | Sgr::ChangeBackgroundColor(_)
+            | Sgr::DefaultBackgroundColor => {}
+        }
+
+        // Only override the decorations of the character style if they are changed.
+        let changes_decorations = matches!(
+            sgr,
+            Sgr::Reset
+                | Sgr::Underline
+                | Sgr::DoubleUnderline
+                | Sgr::UnderlineOff
+                | Sgr::ChangeUnderlineColor(_)
+                | Sgr::DefaultUnderlineColor
+                | Sgr::CrossedOut
+                | Sgr::NotCrossedOut
+        );
+        if !changes_decorations {
+            return;
+        }
+
+        renderer.set_underline_color(if self.font_draws_underline() {
+            self.underline_color()
+        } else {
+            DecorationColor::None
+        });
+        renderer.set_strikethrough_color(if self.font_draws_strikethrough() {
+            self.strikethrough_color()
+        } else {
+            DecorationColor::None
+        });
+    }
+
+    /// Returns `true` if the text is double underlined.
+    pub fn double_underline(&self) -> bool {
+        self.underline == Underline::Double
+    }
+
+    /// Returns `true` if blinking text is in the off phase of the current tick.
+    fn blinked_off(&self) -> bool {
+        let interval = self.blink_interval.max(1);
+        let period = match self.blink {
+            Blink::None => return false,
+            Blink::Rapid => interval,
+            Blink::Slow => interval.saturating_mul(2),
+        };
+
+        (self.tick / period) % 2 == 1
+    }
+
+    /// Returns the character style used to draw blinking text in its off phase.
+    ///
+    /// Returns `None` if the text should be drawn using the unchanged character style.
+    pub fn blink_style<F>(&self, renderer: &F) -> Option<F>
+    where
+        F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle + Clone,
+        <F as CharacterStyle>::Color: From<Rgb>,
+    {
+        if !self.blinked_off() {
+            return None;
+        }
+
+        let mut style = renderer.clone();
+        match self.blink_mode {
+            BlinkMode::Hide => {
+                style.set_text_color(None);
+                style.set_underline_color(DecorationColor::None);
+                style.set_strikethrough_color(DecorationColor::None);
+            }
+            BlinkMode::Reverse => {
+                let (text_color, background_color) = style_colors(renderer);
+                style.set_text_color(background_color);
+                style.set_background_color(text_color);
+            }
+        }
+
+        Some(style)
+    }
+
+    fn font_draws_underline(&self) -> bool {
+        self.underline == Underline::Single && uses_font_geometry(&self.underline_style)
+    }
+
+    fn font_draws_strikethrough(&self) -> bool {
+        self.strikethrough && uses_font_geometry(&self.strikethrough_style)
+    }
+
+    fn underline_color<C: From<Rgb>>(&self) -> DecorationColor<C> {
+        match self.underline_color.or(self.underline_style.color) {
+            Some(color) => DecorationColor::Custom(color.into()),
+            None => DecorationColor::TextColor,
+        }
+    }
+
+    fn strikethrough_color<C: From<Rgb>>(&self) -> DecorationColor<C> {
+        match self.strikethrough_style.color {
+            Some(color) => DecorationColor::Custom(color.into()),
+            None => DecorationColor::TextColor,
+        }
+    }
+
+    /// Draws the lines that are not drawn by the font under or across a piece of text.
+    ///
+    /// `position` is the start of the text on the baseline.
+    pub fn draw_decorations<F, D>(
+        &self,
+        renderer: &F,
+        position: Point,
+        width: u32,
+        display: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle + Clone,
+        <F as CharacterStyle>::Color: From<Rgb>,
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+    {
+        if width == 0 || (self.blink_mode == BlinkMode::Hide && self.blinked_off()) {
+            return Ok(());
+        }
+
+        // The lines are drawn as whitespace, which only draws the background and the decorations.
+        let mut style = renderer.clone();
+        style.set_background_color(None);
+
+        if self.underline != Underline::None && !self.font_draws_underline() {
+            style.set_underline_color(self.underline_color());
+            style.set_strikethrough_color(DecorationColor::None);
+
+            let thickness = self.underline_style.extra_thickness as i32 + 1;
+            let top = position + Point::new(0, self.underline_style.offset);
+            draw_line(&style, top, thickness, width, display)?;
+
+            // The second line is drawn below the first one. The measurement of double underlined
+            // lines reserves space for it.
+            if self.underline == Underline::Double {
+                let top = top + Point::new(0, thickness + 1);
+                draw_line(&style, top, thickness, width, display)?;
+            }
+        }
+
+        if self.strikethrough && !self.font_draws_strikethrough() {
+            style.set_underline_color(DecorationColor::None);
+            style.set_strikethrough_color(self.strikethrough_color());
+
+            let thickness = self.strikethrough_style.extra_thickness as i32 + 1;
+            let top = position + Point::new(0, self.strikethrough_style.offset);
+            draw_line(&style, top, thickness, width, display)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if the line is drawn where the font places it.
+fn uses_font_geometry(decoration: &Decoration) -> bool {
+    decoration.offset == 0 && decoration.extra_thickness == 0
+}
+
+/// Returns the text and background colors of a character style.
+///
+/// Character styles can't be asked for their colors, so they are read back from the pixels of
+/// drawn whitespace. The text color is the color of an underline using the text color.
+fn style_colors<F>(
+    style: &F,
+) -> (
+    Option<<F as CharacterStyle>::Color>,
+    Option<<F as CharacterStyle>::Color>,
+)
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle + Clone,
+{
+    let mut style = style.clone();
+    style.set_underline_color(DecorationColor::None);
+    style.set_strikethrough_color(DecorationColor::None);
+
+    let mut probe = ColorProbe(None);
+    let _ = style.draw_whitespace(1, Point::zero(), Baseline::Top, &mut probe);
+    let background_color = probe.0;
+
+    style.set_background_color(None);
+    style.set_underline_color(DecorationColor::TextColor);
+
+    let mut probe = ColorProbe(None);
+    let _ = style.draw_whitespace(1, Point::zero(), Baseline::Top, &mut probe);
+    let text_color = probe.0;
+
+    (text_color, background_color)
+}
+
+/// A draw target that records the color of the first drawn pixel.
+struct ColorProbe<C>(Option<C>);
+
+impl<C: PixelColor> Dimensions for ColorProbe<C> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(
+            Point::new(i32::MIN / 2, i32::MIN / 2),
+            Size::new(u32::MAX, u32::MAX),
+        )
+    }
+}
+
+impl<C: PixelColor> DrawTarget for ColorProbe<C> {
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        if self.0.is_none() {
+            self.0 = pixels.into_iter().next().map(|Pixel(_, color)| color);
+        }
+        Ok(())
+    }
+}
+
+/// Draws the decoration of `style` repeatedly, moving it down by a pixel each time.
+fn draw_line<F, D>(
+    style: &F,
+    position: Point,
+    thickness: i32,
+    width: u32,
+    display: &mut D,
+) -> Result<(), D::Error>
+where
+    F: TextRenderer,
+    D: DrawTarget<Color = F::Color>,
+{
+    for dy in 0..thickness {
+        style.draw_whitespace(
+            width,
+            position + Point::new(0, dy),
+            Baseline::Alphabetic,
+            display,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle, MonoTextStyleBuilder},
+        pixelcolor::Rgb888,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use super::*;
+    use crate::{animation::Animation, style::TextBoxStyleBuilder, utils::test::size_for, TextBox};
+
+    fn draw(text: &str, underline: Decoration, strikethrough: Decoration) -> MockDisplay<Rgb888> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 2)),
+            MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+            TextBoxStyleBuilder::new()
+                .underline(underline)
+                .strikethrough(strikethrough)
+                .build(),
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display
+    }
+
+    #[test]
+    fn parse_underline_sgr() {
+        assert_eq!(try_parse_sgr(&[21]), Some(Sgr::DoubleUnderline));
+        assert_eq!(
+            try_parse_sgr(&[58, 2, 1, 2, 3]),
+            Some(Sgr::ChangeUnderlineColor(Rgb::new(1, 2, 3)))
+        );
+        assert_eq!(
+            try_parse_sgr(&[58, 5, 1]),
+            Some(Sgr::ChangeUnderlineColor(standard_to_rgb(1)))
+        );
+        assert_eq!(try_parse_sgr(&[58]), None);
+        assert_eq!(try_parse_sgr(&[59]), Some(Sgr::DefaultUnderlineColor));
+    }
+
+    #[test]
+    fn parse_blink_sgr() {
+        assert_eq!(try_parse_sgr(&[5]), Some(Sgr::SlowBlink));
+        assert_eq!(try_parse_sgr(&[6]), Some(Sgr::RapidBlink));
+        assert_eq!(try_parse_sgr(&[25]), Some(Sgr::BlinkOff));
+    }
+
+    #[test]
+    fn underline_color() {
+        let display = draw(
+            "\x1b[58;2;255;0;0ma\x1b[4mb",
+            Decoration::default(),
+            Decoration::default(),
+        );
+
+        // Changing the underline color doesn't underline the text.
+        display.assert_pattern(&[
+            "            ",
+            "       W    ",
+            "       W    ",
+            "  WWW  WWW  ",
+            " W  W  W  W ",
+            " W  W  W  W ",
+            "  WWW  WWW  ",
+            "            ",
+            "      RRRRRR",
+        ]);
+    }
+
+    #[test]
+    fn double_underline() {
+        let display = draw("\x1b[21mab", Decoration::default(), Decoration::default());
+
+        display.assert_pattern(&[
+            "            ",
+            "       W    ",
+            "       W    ",
+            "  WWW  WWW  ",
+            " W  W  W  W ",
+            " W  W  W  W ",
+            "  WWW  WWW  ",
+            "            ",
+            "WWWWWWWWWWWW",
+            "            ",
+            "WWWWWWWWWWWW",
+        ]);
+    }
+
+    #[test]
+    fn double_underlined_lines_are_taller() {
+        let character_style = MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE);
+        let style = TextBoxStyle::default();
+
+        assert_eq!(style.measure_text_height(&character_style, "ab", 60), 9);
+        assert_eq!(
+            style.measure_text_height(&character_style, "\x1b[21mab", 60),
+            11
+        );
+        assert_eq!(
+            style.measure_text_height(&character_style, "\x1b[21ma\nb", 60),
+            22
+        );
+        assert_eq!(
+            style.measure_text_height(&character_style, "\x1b[21ma\x1b[24m\nb", 60),
+            20
+        );
+    }
+
+    #[test]
+    fn decoration_options() {
+        let display = draw(
+            "a\x1b[4m\x1b[9mb",
+            Decoration::new(None, -1, 1),
+            Decoration::new(Some(Rgb::new(0, 255, 0)), 0, 0),
+        );
+
+        display.assert_pattern(&[
+            "            ",
+            "       W    ",
+            "       W    ",
+            "  WWW  WWW  ",
+            " W  W GGGGGG",
+            " W  W  W  W ",
+            "  WWW  WWW  ",
+            "      WWWWWW",
+            "      WWWWWW",
+        ]);
+    }
+
+    #[test]
+    fn underline_off_ends_double_underline() {
+        let display = draw(
+            "\x1b[21ma\x1b[24mb",
+            Decoration::new(None, -1, 0),
+            Decoration::default(),
+        );
+
+        display.assert_pattern(&[
+            "            ",
+            "       W    ",
+            "       W    ",
+            "  WWW  WWW  ",
+            " W  W  W  W ",
+            " W  W  W  W ",
+            "  WWW  WWW  ",
+            "WWWWWW      ",
+            "            ",
+            "WWWWWW      ",
+        ]);
+    }
+
+    fn draw_at_tick(text: &str, blink_mode: BlinkMode, tick: u32) -> MockDisplay<Rgb888> {
+        let mut text_box = TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+            MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+            TextBoxStyleBuilder::new().blink_mode(blink_mode).build(),
+        );
+        text_box.animation = Animation::new().tick(tick);
+
+        let mut display = MockDisplay::new();
+        text_box.draw(&mut display).unwrap();
+
+        display
+    }
+
+    #[test]
+    fn blinking_text_is_hidden_when_switched_off() {
+        let text = "a\x1b[6mb";
+        let pattern_on = [
+            "            ",
+            "       W    ",
+            "       W    ",
+            "  WWW  WWW  ",
+            " W  W  W  W ",
+            " W  W  W  W ",
+            "  WWW  WWW  ",
+        ];
+        let pattern_off = [
+            "            ",
+            "            ",
+            "            ",
+            "  WWW       ",
+            " W  W       ",
+            " W  W       ",
+            "  WWW       ",
+        ];
+
+        draw_at_tick(text, BlinkMode::Hide, 0).assert_pattern(&pattern_on);
+        draw_at_tick(text, BlinkMode::Hide, 1).assert_pattern(&pattern_off);
+        draw_at_tick(text, BlinkMode::Hide, 2).assert_pattern(&pattern_on);
+    }
+
+    #[test]
+    fn slow_blinking_changes_half_as_often() {
+        let text = "\x1b[5ma";
+        let visible = |tick| {
+            !draw_at_tick(text, BlinkMode::Hide, tick)
+                .affected_area()
+                .is_zero_sized()
+        };
+
+        assert!(visible(0));
+        assert!(visible(1));
+        assert!(!visible(2));
+        assert!(!visible(3));
+        assert!(visible(4));
+    }
+
+    #[test]
+    fn reverse_video_blinking() {
+        let text = "\x1b[38;2;255;0;0m\x1b[48;2;255;255;255m\x1b[5ma";
+
+        let mut expected = MockDisplay::new();
+        TextBox::new(
+            "a",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+            MonoTextStyleBuilder::new()
+                .font(&FONT_6X9)
+                .text_color(Rgb888::WHITE)
+                .background_color(Rgb888::RED)
+                .build(),
+        )
+        .draw(&mut expected)
+        .unwrap();
+
+        draw_at_tick(text, BlinkMode::Reverse, 2).assert_eq(&expected);
+    }
+
+    #[test]
+    fn reverse_video_blinking_uses_the_colors_of_the_character_style() {
+        let mut expected = MockDisplay::new();
+        TextBox::new(
+            "a",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 2, 1)),
+            MonoTextStyleBuilder::new()
+                .font(&FONT_6X9)
+                .background_color(Rgb888::WHITE)
+                .build(),
+        )
+        .draw(&mut expected)
+        .unwrap();
+
+        let display = draw_at_tick("\x1b[5ma", BlinkMode::Reverse, 2);
+        assert_eq!(display.affected_area(), expected.affected_area());
+        display.assert_eq(&expected);
+    }
+}
@@ -1,5 +1,11 @@
 //! Cursor to track rendering position.
-use embedded_graphics::{geometry::Point, primitives::Rectangle, text::LineHeight};
+use embedded_graphics::{
+    geometry::Point,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, LineHeight},
+};
+
+use crate::style::{TabAlignment, TabStops, TextBoxStyle};
 
 /// Tracks position within a line.
 #[derive(Debug, Clone)]
@@ -8,15 +14,17 @@ pub struct LineCursor {
     width: u32,
     position: u32,
     tab_width: u32,
+    tab_stops: TabStops,
 }
 
 impl LineCursor {
     /// Creates a new object whose position isn't important.
-    pub fn new(width: u32, tab_width: u32) -> Self {
+    pub fn new(width: u32, tab_width: u32, tab_stops: TabStops) -> Self {
         Self {
             start: Point::zero(),
             width,
             tab_width,
+            tab_stops,
             position: 0,
         }
     }
@@ -32,6 +40,18 @@ impl LineCursor {
         next_tab_pos - self.position
     }
 
+    /// Returns the distance to, and the alignment of the next explicit tab stop.
+    ///
+    /// Returns `None` if there are no explicit tab stops after the current position.
+    #[inline]
+    pub fn next_tab_stop(&self) -> Option<(u32, TabAlignment)> {
+        self.tab_stops
+            .as_slice()
+            .iter()
+            .find(|stop| stop.position > self.position)
+            .map(|stop| (stop.position - self.position, stop.alignment))
+    }
+
     /// Returns the width of the textbox
     #[inline]
     #[must_use]
@@ -95,36 +115,85 @@ pub struct Cursor {
 
     line_height: i32,
     line_spacing: i32,
+
+    /// The line height option of the text box style, used to calculate the line spacing.
+    line_height_setting: LineHeight,
+
+    paragraph_spacing: i32,
     tab_width: u32,
+    tab_stops: TabStops,
+    first_line_indent: u32,
+    hanging_indent: u32,
+
+    /// Whether the current line is the first line of a paragraph.
+    paragraph_start: bool,
 }
 
 impl Cursor {
     /// Creates a new `Cursor` object located at the top left of the given bounding [`Rectangle`].
     #[inline]
     #[must_use]
-    pub fn new(
+    pub fn new<S, A, V, H>(
         bounds: Rectangle,
-        base_line_height: u32,
-        line_height: LineHeight,
-        tab_width: u32,
-    ) -> Self {
+        character_style: &S,
+        style: &TextBoxStyle<A, V, H>,
+    ) -> Self
+    where
+        S: TextRenderer,
+    {
+        let base_line_height = character_style.line_height();
         Self {
             y: bounds.top_left.y,
             line_height: base_line_height.min(i32::MAX as u32) as i32,
-            line_spacing: line_height
+            line_spacing: style
+                .line_height
                 .to_absolute(base_line_height)
                 .min(i32::MAX as u32) as i32,
+            line_height_setting: style.line_height,
+            paragraph_spacing: style.paragraph_spacing.min(i32::MAX as u32) as i32,
             bounds,
-            tab_width,
+            tab_width: style.tab_size.into_pixels(character_style),
+            tab_stops: style.tab_stops,
+            first_line_indent: style.first_line_indent,
+            hanging_indent: style.hanging_indent,
+            paragraph_start: true,
         }
     }
 
+    /// Returns whether the current line is the first line of a paragraph.
+    #[inline]
+    #[must_use]
+    pub fn paragraph_start(&self) -> bool {
+        self.paragraph_start
+    }
+
+    /// Sets whether the current line is the first line of a paragraph.
+    #[inline]
+    pub fn set_paragraph_start(&mut self, paragraph_start: bool) {
+        self.paragraph_start = paragraph_start;
+    }
+
+    /// Returns the indentation of the current line.
+    #[inline]
+    #[must_use]
+    pub fn indent(&self) -> u32 {
+        if self.paragraph_start {
+            self.first_line_indent
+        } else {
+            self.hanging_indent
+        }
+        .min(self.bounds.size.width)
+    }
+
     pub fn line(&self) -> LineCursor {
+        let indent = self.indent();
+
         LineCursor {
-            start: Point::new(self.bounds.top_left.x, self.y),
-            width: self.bounds.size.width,
+            start: Point::new(self.bounds.top_left.x + indent as i32, self.y),
+            width: self.bounds.size.width - indent,
             position: 0,
             tab_width: self.tab_width,
+            tab_stops: self.tab_stops,
         }
     }
 
@@ -155,10 +224,33 @@ impl Cursor {
         self.line_height
     }
 
+    /// Sets the height of the current line, measured from its contents.
+    ///
+    /// The distance to the next line is calculated from this height.
+    #[inline]
+    pub fn set_line_height(&mut self, line_height: u32) {
+        self.line_height = line_height.min(i32::MAX as u32) as i32;
+        self.line_spacing = self
+            .line_height_setting
+            .to_absolute(line_height)
+            .min(i32::MAX as u32) as i32;
+    }
+
     /// Starts a new line.
     #[inline]
     pub fn new_line(&mut self) {
         self.y += self.line_spacing;
+        self.paragraph_start = false;
+    }
+
+    /// Starts a new paragraph.
+    ///
+    /// In addition to starting a new line, this inserts paragraph spacing and makes the next line
+    /// use the first line indentation.
+    #[inline]
+    pub fn new_paragraph(&mut self) {
+        self.y += self.line_spacing + self.paragraph_spacing;
+        self.paragraph_start = true;
     }
 
     /// Returns whether the cursor is completely in the bounding box.
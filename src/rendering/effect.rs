@@ -0,0 +1,66 @@
+//! Draw target adapter for text effects.
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point},
+    pixelcolor::PixelColor,
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// Draws every pixel moved by an offset, using the color of the effect.
+pub(crate) struct EffectDrawTarget<'a, D, C> {
+    parent: &'a mut D,
+    offset: Point,
+    color: C,
+}
+
+impl<'a, D, C> EffectDrawTarget<'a, D, C> {
+    /// Creates a new adapter that draws into `parent`.
+    pub fn new(parent: &'a mut D, offset: Point, color: C) -> Self {
+        Self {
+            parent,
+            offset,
+            color,
+        }
+    }
+}
+
+impl<D, C> Dimensions for EffectDrawTarget<'_, D, C>
+where
+    D: Dimensions,
+{
+    fn bounding_box(&self) -> Rectangle {
+        let bounds = self.parent.bounding_box();
+
+        Rectangle::new(bounds.top_left - self.offset, bounds.size)
+    }
+}
+
+impl<D, C> DrawTarget for EffectDrawTarget<'_, D, C>
+where
+    D: DrawTarget<Color = C>,
+    C: PixelColor,
+{
+    type Color = C;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let offset = self.offset;
+        let color = self.color;
+
+        self.parent.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, _)| Pixel(point + offset, color)),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, _color: Self::Color) -> Result<(), Self::Error> {
+        let area = Rectangle::new(area.top_left + self.offset, area.size);
+
+        self.parent.fill_solid(&area, self.color)
+    }
+}
@@ -3,14 +3,16 @@ use core::convert::Infallible;
 
 use crate::{
     alignment::{HorizontalTextAlignment, VerticalTextAlignment},
+    animation::Reveal,
     parser::{Parser, Token},
-    rendering::{cursor::LineCursor, line_iter::LineElementParser},
-    style::{color::Rgb, height_mode::HeightMode, TextBoxStyle},
-    utils::str_width,
+    renderer::{style_at, styled_width, Spans, StyledPieces},
+    rendering::{cursor::LineCursor, effect::EffectDrawTarget, line_iter::LineElementParser},
+    style::{color::Rgb, height_mode::HeightMode, LineMeasurement, TextBoxStyle, TextEffect},
 };
 use embedded_graphics::{
-    draw_target::DrawTarget,
-    geometry::Point,
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Point, Size},
+    primitives::Rectangle,
     text::{
         renderer::{CharacterStyle, TextRenderer},
         Baseline,
@@ -19,7 +21,7 @@ use embedded_graphics::{
 };
 
 #[cfg(feature = "ansi")]
-use super::ansi::Sgr;
+use super::ansi::{Attributes, Sgr};
 use super::{line_iter::ElementHandler, space_config::UniformSpaceConfig};
 
 /// Render a single line of styled text.
@@ -30,6 +32,9 @@ where
 {
     cursor: LineCursor,
     state: LineRenderState<'a, S, A, V, H>,
+
+    /// The text and the measurement of the line, if already known.
+    line: Option<(&'a str, LineMeasurement)>,
 }
 
 #[derive(Debug, Clone)]
@@ -39,8 +44,14 @@ where
 {
     pub parser: Parser<'a>,
     pub character_style: S,
+    /// Spans that change the character style of parts of the text.
+    pub spans: Option<&'a dyn Spans<S>>,
     pub style: TextBoxStyle<A, V, H>,
     pub carried_token: Option<Token<'a>>,
+    /// The part of the text that is left to be revealed.
+    pub reveal: Option<Reveal>,
+    #[cfg(feature = "ansi")]
+    pub attributes: Attributes,
 }
 
 impl<S, A, V, H> LineRenderState<'_, S, A, V, H>
@@ -50,6 +61,15 @@ where
     pub fn is_finished(&self) -> bool {
         self.carried_token.is_none() && self.parser.is_empty()
     }
+
+    /// Returns `true` if the next line starts with an active double underline.
+    pub fn double_underline(&self) -> bool {
+        #[cfg(feature = "ansi")]
+        return self.attributes.double_underline();
+
+        #[cfg(not(feature = "ansi"))]
+        false
+    }
 }
 
 impl<'a, F, A, V, H> StyledLineRenderer<'a, F, A, V, H>
@@ -61,39 +81,95 @@ where
     /// Creates a new line renderer.
     #[inline]
     pub fn new(cursor: LineCursor, state: LineRenderState<'a, F, A, V, H>) -> Self {
-        Self { cursor, state }
+        Self {
+            cursor,
+            state,
+            line: None,
+        }
+    }
+
+    /// Sets the text and the measurement of the line, so the renderer doesn't need to measure it.
+    ///
+    /// `line` must be the text that is consumed from the parser while rendering the line.
+    #[inline]
+    pub fn with_measurement(mut self, line: &'a str, measurement: LineMeasurement) -> Self {
+        self.line = Some((line, measurement));
+        self
+    }
+}
+
+/// Returns the part of `text` that is revealed, and counts its characters as revealed.
+fn take_revealed<'t>(reveal: &mut Option<Reveal>, text: &'t str) -> &'t str {
+    match reveal {
+        Some(Reveal::Characters(left)) => {
+            let end = text
+                .char_indices()
+                .nth(*left as usize)
+                .map_or(text.len(), |(index, _)| index);
+            let text = &text[..end];
+            *left -= text.chars().count() as u32;
+
+            text
+        }
+        _ => text,
     }
 }
 
 struct RenderElementHandler<'a, F, D> {
     style: &'a mut F,
+    spans: Option<&'a dyn Spans<F>>,
     display: &'a mut D,
+    /// The text effect and its color, if the handler draws the effect instead of the text.
+    effect: Option<(TextEffect, Rgb)>,
+    reveal: &'a mut Option<Reveal>,
+    #[cfg(feature = "ansi")]
+    attributes: &'a mut Attributes,
+
+    /// Position of the cursor on the baseline of the line.
     pos: Point,
 }
 
 impl<'a, F, D> ElementHandler for RenderElementHandler<'a, F, D>
 where
-    F: CharacterStyle + TextRenderer,
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle + Clone,
     <F as CharacterStyle>::Color: From<Rgb>,
     D: DrawTarget<Color = <F as TextRenderer>::Color>,
 {
     type Error = D::Error;
 
-    fn measure(&self, st: &str) -> u32 {
-        str_width(self.style, st)
+    fn measure(&self, st: &str, offset: usize) -> u32 {
+        styled_width(self.spans, self.style, st, offset)
     }
 
-    fn whitespace(&mut self, width: u32) -> Result<(), Self::Error> {
-        self.pos = self
-            .style
-            .draw_whitespace(width, self.pos, Baseline::Top, self.display)?;
-        Ok(())
+    fn whitespace(&mut self, width: u32, offset: usize) -> Result<(), Self::Error> {
+        if *self.reveal == Some(Reveal::Characters(0)) {
+            self.pos.x += width as i32;
+            return Ok(());
+        }
+
+        let (span_style, _) = style_at(self.spans, self.style, offset);
+        self.draw(Piece::Whitespace(width), span_style.as_ref())
     }
 
-    fn printed_characters(&mut self, st: &str, _: u32) -> Result<(), Self::Error> {
-        self.pos = self
-            .style
-            .draw_string(st, self.pos, Baseline::Top, self.display)?;
+    fn printed_characters(
+        &mut self,
+        st: &str,
+        width: u32,
+        offset: usize,
+    ) -> Result<(), Self::Error> {
+        let start = self.pos;
+        let revealed = take_revealed(self.reveal, st);
+
+        let base = self.style.clone();
+        for (piece, span_style) in StyledPieces::new(self.spans, &base, revealed, offset) {
+            self.draw(Piece::Text(piece), span_style.as_ref())?;
+        }
+
+        // Text that is not revealed yet still takes up its space.
+        if revealed.len() < st.len() {
+            self.pos.x = start.x + width as i32;
+        }
+
         Ok(())
     }
 
@@ -105,13 +181,126 @@ where
 
     #[cfg(feature = "ansi")]
     fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
-        sgr.apply(self.style);
+        self.attributes.apply(sgr, self.style);
         Ok(())
     }
 }
 
+impl<F, D> RenderElementHandler<'_, F, D>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle + Clone,
+    <F as CharacterStyle>::Color: From<Rgb>,
+    D: DrawTarget<Color = <F as TextRenderer>::Color>,
+{
+    /// Draws a piece of the line, or its text effect, using the character style of its span and
+    /// the current blink phase.
+    fn draw(&mut self, piece: Piece<'_>, span_style: Option<&F>) -> Result<(), D::Error> {
+        let style = span_style.unwrap_or(self.style);
+        #[cfg(feature = "ansi")]
+        let blink_style = self.attributes.blink_style(style);
+        #[cfg(feature = "ansi")]
+        let style = blink_style.as_ref().unwrap_or(style);
+
+        let painter = Painter {
+            style,
+            #[cfg(feature = "ansi")]
+            attributes: self.attributes,
+        };
+
+        self.pos = match self.effect {
+            Some((effect, color)) => {
+                let mut effect_style = style.clone();
+                effect_style.set_background_color(None);
+                let effect_painter = painter.with_style(&effect_style);
+
+                // Every copy of the piece ends at the same position.
+                let mut next = self.pos;
+                for offset in effect.offsets() {
+                    let mut display =
+                        EffectDrawTarget::new(&mut *self.display, offset, color.into());
+                    next = effect_painter.draw(piece, self.pos, &mut display)?;
+                }
+                next
+            }
+            None => painter.draw(piece, self.pos, self.display)?,
+        };
+
+        Ok(())
+    }
+}
+
+/// A piece of a line that is drawn at once.
+#[derive(Copy, Clone)]
+enum Piece<'a> {
+    Whitespace(u32),
+    Text(&'a str),
+}
+
+/// Draws pieces of a line using a character style.
+struct Painter<'a, F> {
+    style: &'a F,
+    #[cfg(feature = "ansi")]
+    attributes: &'a Attributes,
+}
+
+impl<'a, F> Painter<'a, F> {
+    /// Returns a painter that draws using a different character style.
+    fn with_style<'b>(&self, style: &'b F) -> Painter<'b, F>
+    where
+        'a: 'b,
+    {
+        Painter {
+            style,
+            #[cfg(feature = "ansi")]
+            attributes: self.attributes,
+        }
+    }
+}
+
+impl<F> Painter<'_, F>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle + Clone,
+    <F as CharacterStyle>::Color: From<Rgb>,
+{
+    /// Draws a piece and the decorations that the font doesn't draw.
+    ///
+    /// Returns the position after the piece.
+    fn draw<D>(&self, piece: Piece<'_>, position: Point, display: &mut D) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = <F as CharacterStyle>::Color>,
+    {
+        let next = match piece {
+            Piece::Whitespace(width) => {
+                self.style
+                    .draw_whitespace(width, position, Baseline::Alphabetic, display)?
+            }
+            Piece::Text(text) => {
+                self.style
+                    .draw_string(text, position, Baseline::Alphabetic, display)?
+            }
+        };
+
+        #[cfg(feature = "ansi")]
+        self.attributes.draw_decorations(
+            self.style,
+            position,
+            (next.x - position.x) as u32,
+            display,
+        )?;
+
+        Ok(next)
+    }
+}
+
 struct StyleOnlyRenderElementHandler<'a, F> {
     style: &'a mut F,
+    spans: Option<&'a dyn Spans<F>>,
+    reveal: &'a mut Option<Reveal>,
+    #[cfg(feature = "ansi")]
+    attributes: &'a mut Attributes,
+
+    /// Position of the cursor, relative to the start of the line.
+    x: i32,
 }
 
 impl<'a, F> ElementHandler for StyleOnlyRenderElementHandler<'a, F>
@@ -121,13 +310,34 @@ where
 {
     type Error = Infallible;
 
-    fn measure(&self, st: &str) -> u32 {
-        str_width(self.style, st)
+    fn measure(&self, st: &str, offset: usize) -> u32 {
+        styled_width(self.spans, self.style, st, offset)
+    }
+
+    fn whitespace(&mut self, width: u32, _offset: usize) -> Result<(), Self::Error> {
+        self.x += width as i32;
+        Ok(())
+    }
+
+    fn printed_characters(
+        &mut self,
+        st: &str,
+        width: u32,
+        _offset: usize,
+    ) -> Result<(), Self::Error> {
+        take_revealed(self.reveal, st);
+        self.x += width as i32;
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, by: i32) -> Result<(), Self::Error> {
+        self.x += by;
+        Ok(())
     }
 
     #[cfg(feature = "ansi")]
     fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
-        sgr.apply(self.style);
+        self.attributes.apply(sgr, self.style);
         Ok(())
     }
 }
@@ -151,11 +361,15 @@ where
         let LineRenderState {
             mut parser,
             mut character_style,
+            spans,
             style,
             carried_token,
+            mut reveal,
+            #[cfg(feature = "ansi")]
+            mut attributes,
         } = self.state.clone();
 
-        let carried = if display.bounding_box().size.height == 0 {
+        let (carried, line_width) = if display.bounding_box().size.height == 0 {
             // We're outside of the view - no need for a separate measure pass.
             let mut elements = LineElementParser::<'_, '_, _, A>::new(
                 &mut parser,
@@ -164,56 +378,122 @@ where
                 carried_token,
             );
 
-            elements
-                .process(&mut StyleOnlyRenderElementHandler {
-                    style: &mut character_style,
-                })
-                .unwrap()
+            let mut handler = StyleOnlyRenderElementHandler {
+                style: &mut character_style,
+                spans,
+                reveal: &mut reveal,
+                #[cfg(feature = "ansi")]
+                attributes: &mut attributes,
+                x: 0,
+            };
+            let carried = elements.process(&mut handler).unwrap();
+
+            (carried, handler.x)
         } else {
-            // We have to resort to trickery to figure out the string that is rendered as the line.
-            let mut cloned_parser = parser.clone();
-            let lm = style.measure_line(
-                &character_style,
-                &mut cloned_parser,
-                &mut carried_token.clone(),
-                self.cursor.line_width(),
-            );
-
-            let consumed_bytes = parser.as_str().len() - cloned_parser.as_str().len();
-            let line_str = unsafe { parser.as_str().get_unchecked(..consumed_bytes) };
+            let (line_str, lm) = match self.line {
+                Some(line) => line,
+                None => {
+                    // We have to resort to trickery to figure out the string that is rendered as
+                    // the line.
+                    let mut cloned_parser = parser.clone();
+                    let lm = style.measure_line(
+                        &character_style,
+                        spans,
+                        &mut cloned_parser,
+                        &mut carried_token.clone(),
+                        &mut self.state.double_underline(),
+                        self.cursor.line_width(),
+                    );
+
+                    let consumed_bytes = parser.as_str().len() - cloned_parser.as_str().len();
+                    let line_str = unsafe { parser.as_str().get_unchecked(..consumed_bytes) };
+
+                    (line_str, lm)
+                }
+            };
 
             let (left, space_config) = A::place_line(line_str, &character_style, lm);
 
             let mut cursor = self.cursor.clone();
             cursor.move_cursor(left as i32).ok();
 
-            let pos = cursor.pos();
+            // Every run of the line is drawn on the same baseline.
+            let pos = cursor.pos() + Point::new(0, lm.ascent as i32);
+
+            // Only the revealed pixels of the line are drawn.
+            let bounds = display.bounding_box();
+            let revealed_area = match reveal {
+                Some(Reveal::Pixels(left)) => Rectangle::new(
+                    Point::new(pos.x, bounds.top_left.y),
+                    Size::new(left, bounds.size.height),
+                ),
+                _ => bounds,
+            };
+            let mut display = display.clipped(&revealed_area);
+
+            // The effect is drawn behind the whole line, so it doesn't cover the text of
+            // neighboring pieces. It's drawn in a separate pass over a copy of the line state.
+            if let Some(color) = style.text_effect.color() {
+                let mut handler = RenderElementHandler {
+                    style: &mut character_style.clone(),
+                    spans,
+                    display: &mut display,
+                    effect: Some((style.text_effect, color)),
+                    reveal: &mut reveal.clone(),
+                    #[cfg(feature = "ansi")]
+                    attributes: &mut attributes.clone(),
+                    pos,
+                };
+                LineElementParser::<'_, '_, _, A>::new(
+                    &mut parser.clone(),
+                    cursor.clone(),
+                    space_config,
+                    carried_token.clone(),
+                )
+                .process(&mut handler)?;
+            }
+
             let mut elements = LineElementParser::<'_, '_, _, A>::new(
                 &mut parser,
                 cursor,
                 space_config,
                 carried_token,
             );
-
-            elements.process(&mut RenderElementHandler {
+            let mut handler = RenderElementHandler {
                 style: &mut character_style,
-                display,
+                spans,
+                display: &mut display,
+                effect: None,
+                reveal: &mut reveal,
+                #[cfg(feature = "ansi")]
+                attributes: &mut attributes,
                 pos,
-            })?
+            };
+            let carried = elements.process(&mut handler)?;
+
+            (carried, handler.pos.x - pos.x)
         };
 
+        if let Some(Reveal::Pixels(left)) = &mut reveal {
+            *left = left.saturating_sub(line_width.max(0) as u32);
+        }
+
         Ok(LineRenderState {
             parser,
             character_style,
+            spans,
             style,
             carried_token: carried,
+            reveal,
+            #[cfg(feature = "ansi")]
+            attributes,
         })
     }
 }
 
 #[cfg(feature = "ansi")]
 impl Sgr {
-    fn apply<F>(self, renderer: &mut F)
+    pub fn apply<F>(self, renderer: &mut F)
     where
         F: CharacterStyle,
         <F as CharacterStyle>::Color: From<Rgb>,
@@ -238,7 +518,7 @@ impl Sgr {
             Sgr::DefaultBackgroundColor => {
                 renderer.set_background_color(None);
             }
-            Sgr::Underline => {
+            Sgr::Underline | Sgr::DoubleUnderline => {
                 renderer.set_underline_color(DecorationColor::TextColor);
             }
             Sgr::UnderlineOff => {
@@ -250,12 +530,21 @@ impl Sgr {
             Sgr::NotCrossedOut => {
                 renderer.set_strikethrough_color(DecorationColor::None);
             }
+            // The underline color only takes effect on underlined text, and blinking depends on
+            // the tick of the animation. Both are handled by `Attributes`.
+            Sgr::ChangeUnderlineColor(_)
+            | Sgr::DefaultUnderlineColor
+            | Sgr::SlowBlink
+            | Sgr::RapidBlink
+            | Sgr::BlinkOff => {}
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    #[cfg(feature = "ansi")]
+    use crate::rendering::ansi::Attributes;
     use crate::{
         alignment::{HorizontalTextAlignment, VerticalTextAlignment},
         parser::Parser,
@@ -263,7 +552,10 @@ mod test {
             cursor::LineCursor,
             line::{LineRenderState, StyledLineRenderer},
         },
-        style::{color::Rgb, height_mode::HeightMode, TabSize, TextBoxStyle, TextBoxStyleBuilder},
+        style::{
+            color::Rgb, height_mode::HeightMode, TabSize, TabStops, TextBoxStyle,
+            TextBoxStyleBuilder,
+        },
         utils::test::size_for,
     };
     use embedded_graphics::{
@@ -293,13 +585,18 @@ mod test {
         let cursor = LineCursor::new(
             bounds.size.width,
             TabSize::Spaces(4).into_pixels(&character_style),
+            TabStops::new(),
         );
 
         let state = LineRenderState {
             parser,
             character_style,
+            spans: None,
             style,
             carried_token: None,
+            reveal: None,
+            #[cfg(feature = "ansi")]
+            attributes: Attributes::new(&style, 0),
         };
 
         let renderer = StyledLineRenderer::new(cursor, state);
@@ -433,10 +730,11 @@ mod ansi_parser_tests {
     use crate::{
         parser::Parser,
         rendering::{
+            ansi::Attributes,
             cursor::LineCursor,
             line::{LineRenderState, StyledLineRenderer},
         },
-        style::{TabSize, TextBoxStyleBuilder},
+        style::{TabSize, TabStops, TextBoxStyleBuilder},
         utils::test::size_for,
     };
     use embedded_graphics::{
@@ -464,12 +762,16 @@ mod ansi_parser_tests {
         let cursor = LineCursor::new(
             size_for(&FONT_6X9, 7, 1).width,
             TabSize::Spaces(4).into_pixels(&character_style),
+            TabStops::new(),
         );
         let state = LineRenderState {
             parser,
             character_style,
+            spans: None,
             style,
             carried_token: None,
+            reveal: None,
+            attributes: Attributes::new(&style, 0),
         };
         StyledLineRenderer::new(cursor, state)
             .draw(&mut display)
@@ -7,6 +7,7 @@ use crate::{
     alignment::HorizontalTextAlignment,
     parser::{Parser, Token, SPEC_CHAR_NBSP},
     rendering::{cursor::LineCursor, space_config::SpaceConfig},
+    style::TabAlignment,
 };
 use core::marker::PhantomData;
 
@@ -32,19 +33,29 @@ pub struct LineElementParser<'a, 'b, SP, A> {
     empty: bool,
 }
 
+/// Receives the elements of a line.
+///
+/// Strings and whitespace come with the byte offset in the text where they start, which is used
+/// to look up their character style. Strings that are not part of the text, like the hyphen of a
+/// soft hyphen, use the offset of the token they are drawn for.
 pub trait ElementHandler {
     type Error;
 
     /// Returns the width of the given string in pixels.
-    fn measure(&self, st: &str) -> u32;
+    fn measure(&self, st: &str, offset: usize) -> u32;
 
     /// A whitespace block with the given width.
-    fn whitespace(&mut self, _width: u32) -> Result<(), Self::Error> {
+    fn whitespace(&mut self, _width: u32, _offset: usize) -> Result<(), Self::Error> {
         Ok(())
     }
 
     /// A string of printable characters.
-    fn printed_characters(&mut self, _st: &str, _width: u32) -> Result<(), Self::Error> {
+    fn printed_characters(
+        &mut self,
+        _st: &str,
+        _width: u32,
+        _offset: usize,
+    ) -> Result<(), Self::Error> {
         Ok(())
     }
 
@@ -58,6 +69,12 @@ pub trait ElementHandler {
     fn sgr(&mut self, _sgr: Sgr) -> Result<(), Self::Error> {
         Ok(())
     }
+
+    /// The start of a hyperlink, or the end of one if `uri` is empty.
+    #[cfg(feature = "ansi")]
+    fn hyperlink(&mut self, _uri: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 impl<'a, 'b, SP, A> LineElementParser<'a, 'b, SP, A>
@@ -98,21 +115,22 @@ where
         let mut lookahead = self.parser.clone();
 
         'lookahead: loop {
+            let offset = lookahead.offset();
             match lookahead.next() {
                 Some(Token::Word(w)) => {
-                    let w = handler.measure(w);
+                    let w = handler.measure(w, offset);
 
                     width = width.map_or(Some(w), |acc| Some(acc + w));
                 }
 
                 Some(Token::Break(Some(c))) => {
-                    let w = handler.measure(c);
+                    let w = handler.measure(c, offset);
                     width = width.map_or(Some(w), |acc| Some(acc + w));
                     break 'lookahead;
                 }
 
                 #[cfg(feature = "ansi")]
-                Some(Token::EscapeSequence(_)) => {}
+                Some(Token::EscapeSequence(_)) | Some(Token::Hyperlink(_)) => {}
 
                 _ => break 'lookahead,
             }
@@ -121,6 +139,108 @@ where
         width
     }
 
+    /// Returns the width of a tab character.
+    ///
+    /// If the next tab stop is not left aligned, the width depends on the text between the tab
+    /// and the next tab or end of line, so this function needs to look ahead.
+    fn tab_width<E: ElementHandler>(
+        cursor: &LineCursor,
+        mut lookahead: Parser<'a>,
+        mut spaces: SP,
+        handler: &E,
+    ) -> u32 {
+        let (distance, alignment) = match cursor.next_tab_stop() {
+            Some(stop) => stop,
+            None => return cursor.next_tab_width(),
+        };
+
+        if alignment == TabAlignment::Left {
+            return distance;
+        }
+
+        // Only the text that fits into the line is aligned to the tab stop. `width` is measured
+        // to the end of the last word that fits, `pending` is the whitespace that follows it.
+        let available = cursor.space();
+        let mut width = 0;
+        let mut pending = 0;
+        'lookahead: loop {
+            let offset = lookahead.offset();
+            match lookahead.next() {
+                Some(Token::Word(w)) => {
+                    let (w, ends_lookahead) = match alignment {
+                        TabAlignment::Decimal(separator) => match w.find(separator) {
+                            Some(idx) => (
+                                unsafe {
+                                    // SAFETY: `find` returns a character boundary
+                                    w.get_unchecked(..idx)
+                                },
+                                true,
+                            ),
+                            None => (w, false),
+                        },
+                        _ => (w, false),
+                    };
+
+                    let word_end = width + pending + handler.measure(w, offset);
+                    if word_end > available {
+                        // The word is wrapped, so the line ends before it. A word that is too
+                        // long for the line fills all of it.
+                        if width == 0 {
+                            width = available;
+                        }
+                        pending = 0;
+                        break 'lookahead;
+                    }
+
+                    width = word_end;
+                    pending = 0;
+
+                    if ends_lookahead {
+                        break 'lookahead;
+                    }
+                }
+
+                Some(Token::Whitespace(n)) => pending += spaces.consume(n),
+
+                Some(Token::Break(_)) => {}
+
+                #[cfg(feature = "ansi")]
+                Some(Token::EscapeSequence(_)) | Some(Token::Hyperlink(_)) => {}
+
+                _ => break 'lookahead,
+            }
+        }
+        width += pending;
+
+        let anchor = if alignment == TabAlignment::Center {
+            width / 2
+        } else {
+            width
+        };
+
+        distance.saturating_sub(anchor)
+    }
+
+    /// Returns the next token and the byte offset in the text where it starts.
+    fn next_token(&mut self) -> Option<(Token<'a>, usize)> {
+        match self.first_token.take() {
+            Some(token) => {
+                // The carried token ends where the parser continues.
+                let len = match token {
+                    Token::Word(w) => w.len(),
+                    _ => 0,
+                };
+
+                Some((token, self.parser.offset().saturating_sub(len)))
+            }
+            None => {
+                let offset = self.parser.offset();
+
+                self.parser.next().map(|token| (token, offset))
+            }
+        }
+    }
+
     fn move_cursor(&mut self, by: i32) -> Result<i32, i32> {
         self.cursor.move_cursor(by as i32)
     }
@@ -129,13 +249,17 @@ where
         &mut self,
         handler: &E,
         w: &'a str,
+        offset: usize,
     ) -> (&'a str, Option<&'a str>) {
         let mut width = 0;
         for (idx, c) in w.char_indices() {
-            let char_width = handler.measure(unsafe {
-                // SAFETY: we are working on character boundaries
-                w.get_unchecked(idx..idx + c.len_utf8())
-            });
+            let char_width = handler.measure(
+                unsafe {
+                    // SAFETY: we are working on character boundaries
+                    w.get_unchecked(idx..idx + c.len_utf8())
+                },
+                offset + idx,
+            );
             if !self.cursor.fits_in_line(width + char_width) {
                 return (
                     unsafe {
@@ -158,18 +282,21 @@ where
 
         let mut exit = false;
         while !exit {
+            let offset = lookahead.offset();
             let width = match lookahead.next() {
                 Some(Token::Word(w)) => {
                     exit = true;
-                    handler.measure(w) as i32
+                    handler.measure(w, offset) as i32
                 }
                 Some(Token::Break(Some(w))) => {
                     exit = true;
-                    handler.measure(w) as i32
+                    handler.measure(w, offset) as i32
                 }
 
                 Some(Token::Whitespace(n)) => spaces.consume(n) as i32,
-                Some(Token::Tab) => cursor.next_tab_width() as i32,
+                Some(Token::Tab) => {
+                    Self::tab_width(&cursor, lookahead.clone(), spaces, handler) as i32
+                }
 
                 #[cfg(feature = "ansi")]
                 Some(Token::EscapeSequence(AnsiSequence::CursorForward(by))) => by as i32,
@@ -178,7 +305,7 @@ where
                 Some(Token::EscapeSequence(AnsiSequence::CursorBackward(by))) => -(by as i32),
 
                 #[cfg(feature = "ansi")]
-                Some(Token::EscapeSequence(_)) => continue,
+                Some(Token::EscapeSequence(_)) | Some(Token::Hyperlink(_)) => continue,
 
                 _ => return false,
             };
@@ -195,14 +322,17 @@ where
         &mut self,
         handler: &mut E,
         space_width: i32,
+        offset: usize,
     ) -> Result<Option<Token<'a>>, E::Error> {
         if self.empty && A::IGNORE_LEADING_SPACES {
             return Ok(None);
         }
 
         match self.move_cursor(space_width) {
-            Ok(moved) if self.empty => handler.whitespace(moved as u32)?,
-            Ok(moved) if self.next_word_fits(handler) => handler.whitespace(moved as u32)?,
+            Ok(moved) if self.empty => handler.whitespace(moved as u32, offset)?,
+            Ok(moved) if self.next_word_fits(handler) => {
+                handler.whitespace(moved as u32, offset)?
+            }
 
             Ok(moved) | Err(moved) => {
                 handler.move_cursor(moved)?;
@@ -217,18 +347,20 @@ where
         &mut self,
         handler: &mut E,
     ) -> Result<Option<Token<'a>>, E::Error> {
-        while let Some(token) = self.first_token.take().or_else(|| self.parser.next()) {
+        while let Some((token, offset)) = self.next_token() {
             match token {
                 Token::Whitespace(n) => {
                     let space_width = self.spaces.consume(n) as i32;
-                    if let Some(token) = self.draw_whitespace(handler, space_width)? {
+                    if let Some(token) = self.draw_whitespace(handler, space_width, offset)? {
                         return Ok(Some(token));
                     }
                 }
 
                 Token::Tab => {
-                    let space_width = self.cursor.next_tab_width() as i32;
-                    if let Some(token) = self.draw_whitespace(handler, space_width)? {
+                    let space_width =
+                        Self::tab_width(&self.cursor, self.parser.clone(), self.spaces, handler)
+                            as i32;
+                    if let Some(token) = self.draw_whitespace(handler, space_width, offset)? {
                         return Ok(Some(token));
                     }
                 }
@@ -240,9 +372,9 @@ where
                             let token = if let Some(c) = c {
                                 // If a Break contains a character, display it if the next
                                 // Word token does not fit the line.
-                                let width = handler.measure(c);
+                                let width = handler.measure(c, offset);
                                 if self.move_cursor(width as i32).is_ok() {
-                                    handler.printed_characters(c, width)?;
+                                    handler.printed_characters(c, width, offset)?;
                                     Token::Break(None)
                                 } else {
                                     Token::Word(c)
@@ -259,7 +391,7 @@ where
                 }
 
                 Token::Word(w) => {
-                    let width = handler.measure(w);
+                    let width = handler.measure(w, offset);
                     let (word, remainder) = if self.move_cursor(width as i32).is_ok() {
                         // We can move the cursor here since `process_word()`
                         // doesn't depend on it.
@@ -267,7 +399,7 @@ where
                     } else if self.empty {
                         // This word does not fit into an empty line. Find longest part
                         // that fits and push the rest to the next line.
-                        match self.longest_fitting_substr(handler, w) {
+                        match self.longest_fitting_substr(handler, w, offset) {
                             ("", _) => {
                                 // Weird case where width doesn't permit drawing anything.
                                 // End here to prevent infinite looping.
@@ -281,7 +413,7 @@ where
                     };
 
                     self.empty = false;
-                    self.process_word(handler, word)?;
+                    self.process_word(handler, word, offset)?;
 
                     if let Some(remainder) = remainder {
                         return Ok(Some(Token::Word(remainder)));
@@ -305,10 +437,10 @@ where
                             // [Some text|    ]
                             // Cursor forward 2 characters
                             // [Some text  |  ]
-                            let delta = (n * handler.measure(" ")) as i32;
+                            let delta = (n * handler.measure(" ", offset)) as i32;
                             match self.move_cursor(delta) {
                                 Ok(delta) | Err(delta) => {
-                                    handler.whitespace(delta as u32)?;
+                                    handler.whitespace(delta as u32, offset)?;
                                 }
                             }
                         }
@@ -317,11 +449,11 @@ where
                             // The above poses an issue with variable-width fonts.
                             // If cursor movement ignores the variable width, the cursor
                             // will be placed in positions other than glyph boundaries.
-                            let delta = -((n * handler.measure(" ")) as i32);
+                            let delta = -((n * handler.measure(" ", offset)) as i32);
                             match self.move_cursor(delta) {
                                 Ok(delta) | Err(delta) => {
                                     handler.move_cursor(delta)?;
-                                    handler.whitespace(delta.abs() as u32)?;
+                                    handler.whitespace(delta.abs() as u32, offset)?;
                                     handler.move_cursor(delta)?;
                                 }
                             }
@@ -333,6 +465,9 @@ where
                     }
                 }
 
+                #[cfg(feature = "ansi")]
+                Token::Hyperlink(uri) => handler.hyperlink(uri)?,
+
                 Token::NewLine | Token::CarriageReturn => {
                     // we're done
                     return Ok(Some(token));
@@ -347,6 +482,7 @@ where
         &mut self,
         handler: &mut E,
         w: &str,
+        offset: usize,
     ) -> Result<(), E::Error> {
         match w.char_indices().find(|(_, c)| *c == SPEC_CHAR_NBSP) {
             Some((space_pos, _)) => {
@@ -356,19 +492,20 @@ where
                         // Safety: space_pos must be a character boundary
                         w.get_unchecked(0..space_pos)
                     };
-                    handler.printed_characters(word, handler.measure(word))?;
+                    handler.printed_characters(word, handler.measure(word, offset), offset)?;
                 }
 
-                handler.whitespace(self.spaces.consume(1))?;
+                handler.whitespace(self.spaces.consume(1), offset + space_pos)?;
 
                 // If we have anything after the space...
-                if let Some(word) = w.get(space_pos + SPEC_CHAR_NBSP.len_utf8()..) {
-                    return self.process_word(handler, word);
+                let rest = space_pos + SPEC_CHAR_NBSP.len_utf8();
+                if let Some(word) = w.get(rest..) {
+                    return self.process_word(handler, word, offset + rest);
                 }
             }
 
             None => {
-                handler.printed_characters(w, handler.measure(w))?;
+                handler.printed_characters(w, handler.measure(w, offset), offset)?;
             }
         }
 
@@ -384,7 +521,7 @@ mod test {
     use crate::{
         alignment::LeftAligned,
         rendering::{cursor::Cursor, space_config::UniformSpaceConfig},
-        style::TabSize,
+        style::{TabStop, TabStops, TextBoxStyleBuilder},
         utils::{str_width, test::size_for},
     };
     use embedded_graphics::{
@@ -392,7 +529,7 @@ mod test {
         mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
         pixelcolor::BinaryColor,
         primitives::Rectangle,
-        text::{renderer::TextRenderer, LineHeight},
+        text::renderer::TextRenderer,
     };
 
     #[derive(PartialEq, Eq, Debug)]
@@ -427,16 +564,21 @@ mod test {
     impl<'el, F: TextRenderer> ElementHandler for TestElementHandler<F> {
         type Error = Infallible;
 
-        fn measure(&self, st: &str) -> u32 {
+        fn measure(&self, st: &str, _offset: usize) -> u32 {
             str_width(&self.style, st)
         }
 
-        fn whitespace(&mut self, width: u32) -> Result<(), Self::Error> {
+        fn whitespace(&mut self, width: u32, _offset: usize) -> Result<(), Self::Error> {
             self.elements.push(RenderElement::Space(width));
             Ok(())
         }
 
-        fn printed_characters(&mut self, st: &str, width: u32) -> Result<(), Self::Error> {
+        fn printed_characters(
+            &mut self,
+            st: &str,
+            width: u32,
+            _offset: usize,
+        ) -> Result<(), Self::Error> {
             self.elements
                 .push(RenderElement::String(st.to_owned(), width));
             Ok(())
@@ -468,9 +610,8 @@ mod test {
         let config = UniformSpaceConfig::new(&style);
         let cursor = Cursor::new(
             Rectangle::new(Point::zero(), size_for(&FONT_6X9, max_chars, 1)),
-            style.line_height(),
-            LineHeight::Percent(100),
-            TabSize::Spaces(4).into_pixels(&style),
+            &style,
+            &TextBoxStyleBuilder::new().build(),
         )
         .line();
 
@@ -495,9 +636,8 @@ mod test {
         let config = UniformSpaceConfig::new(&style);
         let cursor = Cursor::new(
             Rectangle::new(Point::zero(), size_for(&FONT_6X9, 1, 1) - Size::new(1, 0)),
-            style.line_height(),
-            LineHeight::Percent(100),
-            TabSize::Spaces(4).into_pixels(&style),
+            &style,
+            &TextBoxStyleBuilder::new().build(),
         )
         .line();
 
@@ -664,6 +804,82 @@ mod test {
             &[RenderElement::string("So", 12)],
         );
     }
+
+    #[test]
+    fn tab_stops() {
+        static TAB_STOPS: [TabStop; 4] = [
+            TabStop::left(12),
+            TabStop::right(60),
+            TabStop::center(90),
+            TabStop::decimal(120),
+        ];
+
+        let mut parser = Parser::parse("a\tb\tcc\tdddd\t1.25\te");
+
+        let style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let config = UniformSpaceConfig::new(&style);
+        let cursor = LineCursor::new(40 * 6, 4 * 6, TabStops::from_slice(&TAB_STOPS));
+
+        let mut handler = TestElementHandler::new(style);
+        let mut line: LineElementParser<'_, '_, _, LeftAligned> =
+            LineElementParser::new(&mut parser, cursor, config, None);
+
+        line.process(&mut handler).unwrap();
+
+        assert_eq!(
+            handler.elements,
+            &[
+                RenderElement::string("a", 6),
+                RenderElement::Space(6),
+                RenderElement::string("b", 6),
+                RenderElement::Space(30),
+                RenderElement::string("cc", 12),
+                RenderElement::Space(18),
+                RenderElement::string("dddd", 24),
+                RenderElement::Space(12),
+                RenderElement::string("1.25", 24),
+                // After the last tab stop, the default tab size is used
+                RenderElement::Space(6),
+                RenderElement::string("e", 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn tab_stop_lookahead_stops_at_the_end_of_the_line() {
+        static TAB_STOPS: [TabStop; 1] = [TabStop::right(60)];
+
+        let mut parser = Parser::parse("a\tbb cccccccc");
+
+        let style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let config = UniformSpaceConfig::new(&style);
+        let cursor = LineCursor::new(10 * 6, 4 * 6, TabStops::from_slice(&TAB_STOPS));
+
+        let mut handler = TestElementHandler::new(style);
+        let mut line: LineElementParser<'_, '_, _, LeftAligned> =
+            LineElementParser::new(&mut parser, cursor, config, None);
+
+        line.process(&mut handler).unwrap();
+
+        // "cccccccc" is wrapped, so only "bb" is aligned to the tab stop.
+        assert_eq!(
+            handler.elements,
+            &[
+                RenderElement::string("a", 6),
+                RenderElement::Space(42),
+                RenderElement::string("bb", 12),
+                RenderElement::MoveCursor(0),
+            ]
+        );
+    }
 }
 
 #[cfg(all(test, feature = "ansi"))]
@@ -1,25 +1,32 @@
 //! Pixel iterators used for text rendering.
 #[cfg(feature = "ansi")]
-mod ansi;
+pub(crate) mod ansi;
 pub(crate) mod cursor;
+mod effect;
 mod line;
 pub(crate) mod line_iter;
+pub(crate) mod rotation;
 pub(crate) mod space_config;
+mod vertical;
 
+#[cfg(feature = "ansi")]
+use crate::rendering::ansi::Attributes;
 use crate::{
     alignment::{HorizontalTextAlignment, VerticalTextAlignment},
+    layout::LineRecord,
     parser::{Parser, Token},
     rendering::{
         cursor::Cursor,
         line::{LineRenderState, StyledLineRenderer},
+        rotation::RotatedDrawTarget,
     },
-    style::{color::Rgb, height_mode::HeightMode},
+    style::{color::Rgb, height_mode::HeightMode, Rotation, WritingMode},
     TextBox,
 };
 use embedded_graphics::{
     draw_target::{DrawTarget, DrawTargetExt},
     prelude::{Point, Size},
-    primitives::Rectangle,
+    primitives::{Primitive, PrimitiveStyleBuilder, Rectangle, RoundedRectangle, StrokeAlignment},
     text::renderer::{CharacterStyle, TextRenderer},
     Drawable,
 };
@@ -40,27 +47,116 @@ where
         &self,
         display: &mut D,
     ) -> Result<&'a str, D::Error> {
-        let mut cursor = Cursor::new(
-            self.bounds,
-            self.character_style.line_height(),
-            self.style.line_height,
-            self.style.tab_size.into_pixels(&self.character_style),
-        );
+        match self.style.rotation {
+            Rotation::None => self.draw_text(display),
+            rotation => self.draw_text(&mut RotatedDrawTarget::new(display, self.bounds, rotation)),
+        }
+    }
+}
+
+impl<'a, F, A, V, H> TextBox<'a, F, A, V, H>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb>,
+    A: HorizontalTextAlignment,
+    V: VerticalTextAlignment,
+    H: HeightMode,
+{
+    /// Draws the frame and the text in the coordinate system of the text.
+    pub(crate) fn draw_text<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        self.draw_frame(display)?;
+
+        if self.style.writing_mode == WritingMode::Vertical {
+            return self.draw_vertical(display);
+        }
+
+        let mut cursor = Cursor::new(self.text_area(), &self.character_style, &self.style);
 
         V::apply_vertical_alignment(&mut cursor, self);
+        cursor.y += self.animation.scroll_offset;
 
+        self.draw_lines(display, cursor, &[], |_| true)
+    }
+
+    /// Draws the text, starting at the given cursor position.
+    ///
+    /// `lines` is the cached layout of the text. Lines that are not in `lines` are measured before
+    /// they are drawn. Only the lines whose index is accepted by `redraw` are drawn, the others are
+    /// only processed to keep track of the character style.
+    pub(crate) fn draw_lines<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+        mut cursor: Cursor,
+        mut lines: &[LineRecord],
+        redraw: impl Fn(usize) -> bool,
+    ) -> Result<&'a str, D::Error> {
         let mut state = LineRenderState {
             style: self.style,
             character_style: self.character_style.clone(),
+            spans: self.spans,
             parser: Parser::parse(self.text),
             carried_token: None,
+            reveal: self.animation.reveal,
+            #[cfg(feature = "ansi")]
+            attributes: Attributes::new(&self.style, self.animation.tick),
         };
 
         let mut anything_drawn = false;
+        let mut line_index = 0;
+
+        // Lines that are separated by carriage returns are as tall as the tallest of them.
+        let mut cr_height = 0;
         while !state.is_finished() {
             let line_cursor = cursor.line();
+
+            // Only use the cached line if it belongs to the current position in the text.
+            let record = match lines.split_first() {
+                Some((record, rest))
+                    if record.starts_at(self.text, &state.parser, &state.carried_token) =>
+                {
+                    lines = rest;
+                    Some(*record)
+                }
+                _ => {
+                    lines = &[];
+                    None
+                }
+            };
+
+            // The height of the line depends on its contents, so it needs to be measured before
+            // the displayed area can be calculated.
+            let (line_str, lm) = match record {
+                Some(record) => (record.line_str(self.text), record.measurement()),
+                None => {
+                    let mut parser = state.parser.clone();
+                    let lm = self.style.measure_line(
+                        &state.character_style,
+                        state.spans,
+                        &mut parser,
+                        &mut state.carried_token.clone(),
+                        &mut state.double_underline(),
+                        line_cursor.line_width(),
+                    );
+
+                    let start = self.text.len() - state.parser.as_str().len();
+                    let end = self.text.len() - parser.as_str().len();
+
+                    (&self.text[start..end], lm)
+                }
+            };
+            cursor.set_line_height(lm.height().max(cr_height));
+
             let display_range = H::calculate_displayed_row_range(&cursor);
-            let display_size = Size::new(cursor.line_width(), display_range.clone().count() as u32);
+            let display_height = if redraw(line_index) {
+                display_range.clone().count() as u32
+            } else {
+                0
+            };
+            let display_size = Size::new(line_cursor.line_width(), display_height);
+            line_index += 1;
 
             if display_range.start == display_range.end {
                 if anything_drawn {
@@ -80,14 +176,34 @@ where
 
             // FIXME: cropping isn't necessary for whole lines, but make sure not to blow up the
             // binary size as well.
-            let mut display = display.clipped(&Rectangle::new(
+            let mut line_area = Rectangle::new(
                 line_cursor.pos() + Point::new(0, display_range.start),
                 display_size,
-            ));
-            state = StyledLineRenderer::new(line_cursor, state).draw(&mut display)?;
+            );
+            if display_height > 0 {
+                // Text effects can extend past the line, into the space reserved around the text.
+                let margins = self.style.text_effect.margins();
+                line_area = Rectangle::new(
+                    line_area.top_left - Point::new(margins.left as i32, margins.top as i32),
+                    line_area.size + Size::new(margins.horizontal(), margins.vertical()),
+                );
+            }
+            let mut display = display.clipped(&line_area);
+
+            state = StyledLineRenderer::new(line_cursor, state)
+                .with_measurement(line_str, lm)
+                .draw(&mut display)?;
 
-            if state.carried_token != Some(Token::CarriageReturn) {
-                cursor.new_line();
+            match state.carried_token {
+                Some(Token::CarriageReturn) => cr_height = cursor.line_height() as u32,
+                Some(Token::NewLine) => {
+                    cr_height = 0;
+                    cursor.new_paragraph();
+                }
+                _ => {
+                    cr_height = 0;
+                    cursor.new_line();
+                }
             }
         }
 
@@ -95,20 +211,58 @@ where
     }
 }
 
+impl<F, A, V, H> TextBox<'_, F, A, V, H>
+where
+    F: CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb>,
+{
+    /// Draws the background and the border of the text box.
+    pub(crate) fn draw_frame<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<(), D::Error> {
+        let mut style = PrimitiveStyleBuilder::new()
+            .stroke_width(self.style.border_width)
+            .stroke_alignment(StrokeAlignment::Inside);
+
+        if let Some(color) = self.style.background_color {
+            style = style.fill_color(color.into());
+        }
+        if let Some(color) = self.style.border_color {
+            style = style.stroke_color(color.into());
+        }
+
+        let style = style.build();
+        if style.is_transparent() {
+            return Ok(());
+        }
+
+        let radius = self.style.corner_radius;
+        RoundedRectangle::with_equal_corners(self.logical_bounds(), Size::new(radius, radius))
+            .into_styled(style)
+            .draw(display)
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use embedded_graphics::{
         mock_display::MockDisplay,
-        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
-        pixelcolor::BinaryColor,
+        mono_font::{ascii::FONT_6X9, MonoTextStyle, MonoTextStyleBuilder},
+        pixelcolor::{BinaryColor, Rgb888},
         prelude::*,
         primitives::Rectangle,
     };
 
     use crate::{
         alignment::{HorizontalTextAlignment, LeftAligned},
-        style::TextBoxStyleBuilder,
-        utils::test::size_for,
+        style::{
+            color::Rgb,
+            height_mode::{FitToText, ShrinkToText},
+            vertical_overdraw::FullRowsOnly,
+            Padding, TextBoxStyleBuilder, TextEffect,
+        },
+        utils::test::{size_for, TallCapitals},
         TextBox,
     };
 
@@ -177,4 +331,279 @@ pub mod test {
             ],
         );
     }
+
+    #[test]
+    fn paragraph_spacing_and_indentation() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .paragraph_spacing(2)
+            .first_line_indent(6)
+            .build();
+
+        TextBox::with_textbox_style(
+            "a\nb",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 3)),
+            character_style,
+            style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "      ......",
+            "      ......",
+            "      ......",
+            "      ..###.",
+            "      .#..#.",
+            "      .#..#.",
+            "      ..###.",
+            "      ......",
+            "      ......",
+            "            ",
+            "            ",
+            "      ......",
+            "      .#....",
+            "      .#....",
+            "      .###..",
+            "      .#..#.",
+            "      .#..#.",
+            "      .###..",
+            "      ......",
+            "      ......",
+        ]);
+    }
+
+    #[test]
+    fn padding_and_background() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(FitToText)
+            .padding(Padding::new(1, 1, 2, 2))
+            .background_color(Rgb::new(255, 255, 255))
+            .build();
+
+        let text_box = TextBox::with_textbox_style(
+            "a",
+            Rectangle::new(Point::zero(), Size::new(9, 0)),
+            character_style,
+            style,
+        );
+
+        assert_eq!(text_box.bounding_box().size, Size::new(9, 12));
+
+        text_box.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "#########",
+            "##......#",
+            "##......#",
+            "##......#",
+            "##..###.#",
+            "##.#..#.#",
+            "##.#..#.#",
+            "##..###.#",
+            "##......#",
+            "##......#",
+            "#########",
+            "#########",
+        ]);
+    }
+
+    fn draw_with_effect(text_effect: TextEffect, size: Size) -> MockDisplay<Rgb888> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(FitToText)
+            .text_effect(text_effect)
+            .build();
+
+        let mut text_box = TextBox::with_textbox_style(
+            "a",
+            Rectangle::new(Point::zero(), Size::new(100, 0)),
+            MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+            style,
+        );
+        text_box.fit_size();
+        assert_eq!(text_box.bounding_box().size, size);
+
+        text_box.draw(&mut display).unwrap();
+
+        display
+    }
+
+    #[test]
+    fn shadow_effect() {
+        let display = draw_with_effect(
+            TextEffect::Shadow {
+                offset: Point::new(1, 1),
+                color: Rgb::new(255, 0, 0),
+            },
+            Size::new(7, 10),
+        );
+
+        display.assert_pattern(&[
+            "       ", "       ", "       ", "  WWW  ", " W RWR ", " WR WR ", "  WWWR ", "   RRR ",
+        ]);
+    }
+
+    #[test]
+    fn outline_effect() {
+        let display = draw_with_effect(
+            TextEffect::Outline {
+                color: Rgb::new(0, 255, 0),
+            },
+            Size::new(8, 11),
+        );
+
+        display.assert_pattern(&[
+            "        ", "        ", "        ", "  GGGGG ", " GGWWWG ", " GWGGWG ", " GWGGWG ",
+            " GGWWWG ", "  GGGGG ",
+        ]);
+    }
+
+    #[test]
+    fn effect_is_drawn_behind_the_whole_line() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        // The text is moved right by the margin of the effect, and the shadow of "b" falls on "a".
+        let style = TextBoxStyleBuilder::new()
+            .text_effect(TextEffect::Shadow {
+                offset: Point::new(-12, 0),
+                color: Rgb::new(255, 0, 0),
+            })
+            .build();
+
+        TextBox::with_textbox_style(
+            "a b",
+            Rectangle::new(Point::zero(), Size::new(30, 9)),
+            MonoTextStyle::new(&FONT_6X9, Rgb888::WHITE),
+            style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "                              ",
+            "             R           W    ",
+            "             R           W    ",
+            "  RRR        RWWW        WWW  ",
+            " R  R        W  W        W  W ",
+            " R  R        W  W        W  W ",
+            "  RRR        RWWW        WWW  ",
+        ]);
+    }
+
+    #[test]
+    fn frame_shrinks_with_text_box() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .height_mode(ShrinkToText(FullRowsOnly))
+            .background_color(Rgb::new(0, 0, 0))
+            .border_color(Rgb::new(255, 255, 255))
+            .border_width(1)
+            .build();
+
+        let text_box = TextBox::with_textbox_style(
+            "a",
+            Rectangle::new(Point::zero(), Size::new(10, 30)),
+            character_style,
+            style,
+        );
+
+        assert_eq!(text_box.bounding_box().size, Size::new(10, 11));
+
+        text_box.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "##########",
+            "#........#",
+            "#........#",
+            "#........#",
+            "#..###...#",
+            "#.#..#...#",
+            "#.#..#...#",
+            "#..###...#",
+            "#........#",
+            "#........#",
+            "##########",
+        ]);
+    }
+
+    #[test]
+    fn fitting_a_wide_border_does_not_overflow() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .border_color(Rgb::new(255, 255, 255))
+            .border_width(u32::MAX)
+            .build();
+
+        let mut text_box = TextBox::with_textbox_style(
+            "a",
+            Rectangle::new(Point::zero(), Size::new(10, 30)),
+            character_style,
+            style,
+        );
+
+        text_box.fit_height();
+        assert_eq!(text_box.bounding_box().size, Size::new(10, i32::MAX as u32));
+
+        text_box.fit_size();
+        assert_eq!(text_box.bounding_box().size, Size::new(10, i32::MAX as u32));
+    }
+
+    #[test]
+    fn lines_are_as_tall_as_their_contents() {
+        let mut display = MockDisplay::new();
+
+        let character_style = TallCapitals(
+            MonoTextStyleBuilder::new()
+                .font(&FONT_6X9)
+                .text_color(BinaryColor::On)
+                .build(),
+        );
+
+        // The second line is 4 pixels taller than the others.
+        TextBox::new(
+            "a\nB\nc",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 1, 4)),
+            character_style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(&[
+            "      ", "      ", "      ", "  ### ", " #  # ", " #  # ", "  ### ", "      ",
+            "      ", "      ", "      ", "      ", "      ", "      ", "####  ", "#   # ",
+            "####  ", "#   # ", "#   # ", "####  ", "      ", "      ", "      ", "      ",
+            "      ", "  ### ", " #    ", " #    ", "  ### ",
+        ]);
+    }
 }
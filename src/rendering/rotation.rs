@@ -0,0 +1,202 @@
+//! Draw target adapter for rotated text.
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point},
+    pixelcolor::PixelColor,
+    primitives::Rectangle,
+    Pixel,
+};
+
+use crate::style::Rotation;
+
+/// Maps pixels drawn in the rotated coordinate system of a text box to the parent draw target.
+///
+/// The rotated coordinate system has its origin in the top left corner of the rotated bounding
+/// box.
+pub(crate) struct RotatedDrawTarget<'a, D> {
+    parent: &'a mut D,
+    bounds: Rectangle,
+    rotation: Rotation,
+}
+
+impl<'a, D> RotatedDrawTarget<'a, D> {
+    /// Creates a new adapter that draws into `bounds` of `parent`.
+    pub fn new(parent: &'a mut D, bounds: Rectangle, rotation: Rotation) -> Self {
+        Self {
+            parent,
+            bounds,
+            rotation,
+        }
+    }
+
+    fn to_display(&self, point: Point) -> Point {
+        self.rotation.to_display(point, &self.bounds)
+    }
+}
+
+impl<D> Dimensions for RotatedDrawTarget<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), self.rotation.rotate_size(self.bounds.size))
+    }
+}
+
+impl<D, C> DrawTarget for RotatedDrawTarget<'_, D>
+where
+    D: DrawTarget<Color = C>,
+    C: PixelColor,
+{
+    type Color = C;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let rotation = self.rotation;
+        let bounds = self.bounds;
+
+        self.parent.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(rotation.to_display(point, &bounds), color)),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        if let Some(bottom_right) = area.bottom_right() {
+            let area = Rectangle::with_corners(
+                self.to_display(area.top_left),
+                self.to_display(bottom_right),
+            );
+
+            self.parent.fill_solid(&area, color)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        style::{height_mode::FitToText, Rotation, TextBoxStyleBuilder},
+        utils::test::size_for,
+        TextBox,
+    };
+
+    fn assert_rotated(rotation: Rotation, size: Size, pattern: &[&str]) {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new().rotation(rotation).build();
+
+        TextBox::with_textbox_style(
+            "ab",
+            Rectangle::new(Point::zero(), size),
+            character_style,
+            style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        display.assert_pattern(pattern);
+    }
+
+    #[test]
+    fn rotate_90() {
+        assert_rotated(
+            Rotation::Rotate90,
+            Size::new(9, 12),
+            &[
+                "         ",
+                "   ##    ",
+                "  #  #   ",
+                "  #  #   ",
+                "  ####   ",
+                "         ",
+                "         ",
+                "  ###### ",
+                "  #  #   ",
+                "  #  #   ",
+                "   ##    ",
+                "         ",
+            ],
+        );
+    }
+
+    #[test]
+    fn rotate_180() {
+        assert_rotated(
+            Rotation::Rotate180,
+            size_for(&FONT_6X9, 2, 1),
+            &[
+                "            ",
+                "            ",
+                "  ###  ###  ",
+                " #  #  #  # ",
+                " #  #  #  # ",
+                "  ###  ###  ",
+                "    #       ",
+                "    #       ",
+                "            ",
+            ],
+        );
+    }
+
+    #[test]
+    fn rotate_270() {
+        assert_rotated(
+            Rotation::Rotate270,
+            Size::new(9, 12),
+            &[
+                "         ",
+                "    ##   ",
+                "   #  #  ",
+                "   #  #  ",
+                " ######  ",
+                "         ",
+                "         ",
+                "   ####  ",
+                "   #  #  ",
+                "   #  #  ",
+                "    ##   ",
+                "         ",
+            ],
+        );
+    }
+
+    #[test]
+    fn fit_height_measures_rotated_text() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .rotation(Rotation::Rotate90)
+            .height_mode(FitToText)
+            .build();
+
+        // The height of the bounds is the width of the text.
+        let text_box = TextBox::with_textbox_style(
+            "ab cd",
+            Rectangle::new(Point::zero(), Size::new(100, 12)),
+            character_style,
+            style,
+        );
+
+        assert_eq!(text_box.bounds.size, Size::new(18, 12));
+    }
+}
@@ -0,0 +1,524 @@
+//! Vertical text rendering.
+use crate::{
+    alignment::{HorizontalTextAlignment, VerticalTextAlignment},
+    parser::{Parser, Token},
+    style::{color::Rgb, LineMeasurement, TabSize, TextBoxStyle},
+    utils::str_width,
+    TextBox,
+};
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Point, Size},
+    text::{
+        renderer::{CharacterStyle, TextRenderer},
+        Baseline,
+    },
+};
+
+#[cfg(feature = "ansi")]
+use super::ansi::try_parse_sgr;
+#[cfg(feature = "ansi")]
+use ansi_parser::AnsiSequence;
+
+/// A column of vertical text.
+struct Column<'t> {
+    /// The text of the column, without the newline that ends it.
+    text: &'t str,
+
+    /// The distance between the top of the column and the bottom of its last character.
+    height: u32,
+
+    /// The text after the column.
+    rest: &'t str,
+}
+
+impl<A, V, H> TextBoxStyle<A, V, H> {
+    /// Returns the position of the next tab in a column.
+    ///
+    /// Spaces are as tall as the characters in vertical text.
+    fn next_vertical_tab(&self, y: u32, cell_height: u32) -> u32 {
+        let tab_height = match self.tab_size {
+            TabSize::Pixels(px) => px as u32,
+            TabSize::Spaces(n) => n as u32 * cell_height,
+        }
+        .max(1);
+
+        (y / tab_height + 1).saturating_mul(tab_height)
+    }
+
+    /// Returns the first column of `text`, wrapped at `max_height`.
+    fn next_column<'t, S>(&self, character_style: &S, text: &'t str, max_height: u32) -> Column<'t>
+    where
+        S: TextRenderer,
+    {
+        let cell_height = character_style.line_height();
+        let mut parser = Parser::parse(text);
+        let mut y = 0;
+        let mut height = 0;
+
+        loop {
+            let start = text.len() - parser.as_str().len();
+            match parser.next() {
+                None => {
+                    return Column {
+                        text,
+                        height,
+                        rest: "",
+                    }
+                }
+
+                Some(Token::NewLine) => {
+                    let end = text.len() - parser.as_str().len();
+                    return Column {
+                        text: &text[..start],
+                        height,
+                        rest: &text[end..],
+                    };
+                }
+
+                Some(Token::CarriageReturn) => y = 0,
+
+                Some(Token::Whitespace(n)) => {
+                    y = n.saturating_mul(cell_height).saturating_add(y);
+                }
+
+                Some(Token::Tab) => y = self.next_vertical_tab(y, cell_height),
+
+                Some(Token::Word(word)) => {
+                    for (index, _) in word.char_indices() {
+                        if y != 0 && y.saturating_add(cell_height) > max_height {
+                            // The word is a slice of the text, so its offset can be calculated.
+                            let offset = word.as_ptr() as usize - text.as_ptr() as usize + index;
+                            return Column {
+                                text: &text[..offset],
+                                height,
+                                rest: &text[offset..],
+                            };
+                        }
+
+                        y = y.saturating_add(cell_height);
+                        height = height.max(y);
+                    }
+                }
+
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Returns the number of columns of vertical text, and the height of the tallest column.
+    fn measure_columns<S>(&self, character_style: &S, text: &str, max_height: u32) -> (u32, u32)
+    where
+        S: TextRenderer,
+    {
+        let mut rest = text;
+        let mut columns = 0;
+        let mut height = 0;
+        while !rest.is_empty() {
+            let column = self.next_column(character_style, rest, max_height);
+            columns += 1;
+            height = height.max(column.height);
+            rest = column.rest;
+        }
+
+        (columns, height)
+    }
+
+    /// Returns the width of the given number of columns.
+    fn columns_width(&self, cell_height: u32, columns: u32) -> u32 {
+        if columns == 0 {
+            return 0;
+        }
+
+        self.line_height
+            .to_absolute(cell_height)
+            .saturating_mul(columns - 1)
+            .saturating_add(cell_height)
+    }
+
+    /// Measures vertical text.
+    ///
+    /// Returns the size of the text at the smallest height at which the columns fit into
+    /// `max_width`. If the text doesn't fit, the columns are not wrapped.
+    pub(crate) fn measure_vertical_text_size<S>(
+        &self,
+        character_style: &S,
+        text: &str,
+        max_width: u32,
+    ) -> Size
+    where
+        S: TextRenderer,
+    {
+        let cell_height = character_style.line_height();
+        let measure = |max_height| {
+            let (columns, height) = self.measure_columns(character_style, text, max_height);
+            Size::new(self.columns_width(cell_height, columns), height)
+        };
+
+        let unwrapped = measure(u32::MAX);
+        if unwrapped.width > max_width {
+            return unwrapped;
+        }
+
+        // Taller columns never need more columns, so the smallest height can be searched for.
+        let mut low = cell_height.min(unwrapped.height);
+        let mut high = unwrapped.height;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if measure(mid).width <= max_width {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        measure(high)
+    }
+}
+
+impl<'a, F, A, V, H> TextBox<'a, F, A, V, H>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb>,
+    A: HorizontalTextAlignment,
+    V: VerticalTextAlignment,
+{
+    /// Draws the text top to bottom, in columns progressing from right to left.
+    ///
+    /// Returns the text that did not fit into the text box.
+    pub(crate) fn draw_vertical<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        display: &mut D,
+    ) -> Result<&'a str, D::Error> {
+        let text_area = self.text_area();
+        let mut display = display.clipped(&text_area);
+        let mut character_style = self.character_style.clone();
+
+        let cell_height = character_style.line_height();
+        let column_width = cell_height;
+        let column_spacing = self.style.line_height.to_absolute(cell_height) as i32;
+        let column_height = text_area.size.height;
+
+        // The columns start at the right edge of the text box, so the vertical alignment moves
+        // them to the left.
+        let (columns, _) = self
+            .style
+            .measure_columns(&character_style, self.text, column_height);
+        let text_width = self.style.columns_width(cell_height, columns);
+        let right = text_area.top_left.x + text_area.size.width as i32
+            - V::vertical_offset(text_width, text_area.size.width);
+
+        let mut left = right - column_width as i32;
+        let mut rest = self.text;
+        while !rest.is_empty() {
+            if left < text_area.top_left.x {
+                return Ok(rest);
+            }
+
+            let column = self
+                .style
+                .next_column(&character_style, rest, column_height);
+
+            // The horizontal alignment positions the text inside the column.
+            let (offset, _) = A::place_line(
+                column.text,
+                &character_style,
+                LineMeasurement {
+                    max_line_width: column_height,
+                    width: column.height.min(column_height),
+                    last_line: true,
+                    ascent: 0,
+                    descent: 0,
+                },
+            );
+
+            self.draw_column(
+                column.text,
+                Point::new(left, text_area.top_left.y + offset as i32),
+                &mut character_style,
+                &mut display,
+            )?;
+
+            rest = column.rest;
+            left -= column_spacing;
+        }
+
+        Ok("")
+    }
+
+    /// Draws the characters of a column, starting at `top_left`.
+    fn draw_column<D: DrawTarget<Color = <F as CharacterStyle>::Color>>(
+        &self,
+        text: &str,
+        top_left: Point,
+        character_style: &mut F,
+        display: &mut D,
+    ) -> Result<(), D::Error> {
+        let cell_height = character_style.line_height();
+        let column_width = cell_height;
+        let mut y = 0;
+
+        for token in Parser::parse(text) {
+            match token {
+                Token::CarriageReturn => y = 0,
+
+                Token::Whitespace(n) => y = n.saturating_mul(cell_height).saturating_add(y),
+
+                Token::Tab => y = self.style.next_vertical_tab(y, cell_height),
+
+                Token::Word(word) => {
+                    for (index, c) in word.char_indices() {
+                        let character = &word[index..index + c.len_utf8()];
+                        let width = str_width(character_style, character);
+                        let x = (column_width.saturating_sub(width) / 2) as i32;
+
+                        character_style.draw_string(
+                            character,
+                            top_left + Point::new(x, y as i32),
+                            Baseline::Top,
+                            display,
+                        )?;
+
+                        y = y.saturating_add(cell_height);
+                    }
+                }
+
+                #[cfg(feature = "ansi")]
+                Token::EscapeSequence(AnsiSequence::SetGraphicsMode(vec)) => {
+                    if let Some(sgr) = try_parse_sgr(&vec) {
+                        sgr.apply(character_style);
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        alignment::{BottomAligned, CenterAligned},
+        style::{
+            height_mode::{FitToText, ShrinkToText},
+            vertical_overdraw::FullRowsOnly,
+            TabSize, TextBoxStyleBuilder, WritingMode,
+        },
+        TextBox,
+    };
+
+    fn draw_vertical<'a>(
+        text: &'a str,
+        size: Size,
+        display: &mut MockDisplay<BinaryColor>,
+    ) -> &'a str {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .writing_mode(WritingMode::Vertical)
+            .build();
+
+        TextBox::with_textbox_style(
+            text,
+            Rectangle::new(Point::zero(), size),
+            character_style,
+            style,
+        )
+        .draw(display)
+        .unwrap()
+    }
+
+    #[test]
+    fn columns_progress_right_to_left() {
+        let mut display = MockDisplay::new();
+
+        let remaining = draw_vertical("ab\nc", Size::new(18, 18), &mut display);
+
+        assert_eq!(remaining, "");
+        display.assert_pattern(&[
+            "                  ",
+            "                  ",
+            "                  ",
+            "   ###      ###   ",
+            "  #        #  #   ",
+            "  #        #  #   ",
+            "   ###      ###   ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "           #      ",
+            "           #      ",
+            "           ###    ",
+            "           #  #   ",
+            "           #  #   ",
+            "           ###    ",
+        ]);
+    }
+
+    #[test]
+    fn text_is_wrapped_at_the_bottom() {
+        let mut display = MockDisplay::new();
+
+        let remaining = draw_vertical("abcde", Size::new(18, 18), &mut display);
+
+        assert_eq!(remaining, "e");
+    }
+
+    #[test]
+    fn fit_to_text_uses_the_height_of_the_columns() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .writing_mode(WritingMode::Vertical)
+            .height_mode(FitToText)
+            .build();
+
+        // Two columns fit next to each other, so the text is wrapped after two characters.
+        let text_box = TextBox::with_textbox_style(
+            "abcd",
+            Rectangle::new(Point::zero(), Size::new(18, 0)),
+            character_style,
+            style,
+        );
+        assert_eq!(text_box.bounding_box().size, Size::new(18, 18));
+
+        // A single column has to fit every character.
+        let text_box = TextBox::with_textbox_style(
+            "abcd",
+            Rectangle::new(Point::zero(), Size::new(9, 0)),
+            character_style,
+            style,
+        );
+        assert_eq!(text_box.bounding_box().size, Size::new(9, 36));
+
+        // Newlines start new columns, so the longest line sets the height.
+        let text_box = TextBox::with_textbox_style(
+            "abc\nd",
+            Rectangle::new(Point::zero(), Size::new(18, 0)),
+            character_style,
+            style,
+        );
+        assert_eq!(text_box.bounding_box().size, Size::new(18, 27));
+
+        let mut display = MockDisplay::new();
+        assert_eq!(text_box.draw(&mut display).unwrap(), "");
+    }
+
+    #[test]
+    fn shrink_to_text_uses_the_height_of_the_columns() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .writing_mode(WritingMode::Vertical)
+            .height_mode(ShrinkToText(FullRowsOnly))
+            .build();
+
+        let text_box = TextBox::with_textbox_style(
+            "ab\nc",
+            Rectangle::new(Point::zero(), Size::new(18, 45)),
+            character_style,
+            style,
+        );
+        assert_eq!(text_box.bounding_box().size, Size::new(18, 18));
+    }
+
+    #[test]
+    fn measure_vertical_text() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .writing_mode(WritingMode::Vertical)
+            .tab_size(TabSize::Spaces(2))
+            .build();
+
+        // The columns are as short as possible.
+        assert_eq!(
+            style.measure_text_size(&character_style, "ab\nc", 40),
+            Size::new(27, 9)
+        );
+        assert_eq!(
+            style.measure_text_size(&character_style, "ab\nc", 18),
+            Size::new(18, 18)
+        );
+
+        // The tab moves the next character to the next multiple of two characters.
+        assert_eq!(
+            style.measure_text_size(&character_style, "a\tb", 9),
+            Size::new(9, 27)
+        );
+
+        // Text that doesn't fit is not wrapped.
+        assert_eq!(
+            style.measure_text_size(&character_style, "ab\ncd\ne", 18),
+            Size::new(27, 18)
+        );
+    }
+
+    #[test]
+    fn vertical_text_is_aligned() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .writing_mode(WritingMode::Vertical)
+            .alignment(CenterAligned)
+            .vertical_alignment(BottomAligned)
+            .build();
+
+        TextBox::with_textbox_style(
+            "c",
+            Rectangle::new(Point::zero(), Size::new(18, 27)),
+            character_style,
+            style,
+        )
+        .draw(&mut display)
+        .unwrap();
+
+        // The column is moved to the left edge, the character to the middle of the column.
+        display.assert_pattern(&[
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "                  ",
+            "   ###            ",
+            "  #               ",
+            "  #               ",
+            "   ###            ",
+        ]);
+    }
+}
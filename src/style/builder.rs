@@ -4,9 +4,11 @@ use embedded_graphics::text::LineHeight;
 use crate::{
     alignment::{HorizontalTextAlignment, LeftAligned, TopAligned, VerticalTextAlignment},
     style::{
+        color::Rgb,
         height_mode::{Exact, HeightMode},
         vertical_overdraw::FullRowsOnly,
-        TabSize, TextBoxStyle,
+        BlinkMode, Decoration, Padding, Rotation, TabSize, TabStop, TabStops, TextBoxStyle,
+        TextEffect, WritingMode,
     },
 };
 
@@ -37,6 +39,22 @@ impl TextBoxStyleBuilder<LeftAligned, TopAligned, Exact<FullRowsOnly>> {
                 height_mode: Exact(FullRowsOnly),
                 line_height: LineHeight::Percent(100),
                 tab_size: TabSize::Spaces(4),
+                tab_stops: TabStops::new(),
+                paragraph_spacing: 0,
+                first_line_indent: 0,
+                hanging_indent: 0,
+                padding: Padding::new(0, 0, 0, 0),
+                background_color: None,
+                border_color: None,
+                border_width: 0,
+                corner_radius: 0,
+                rotation: Rotation::None,
+                writing_mode: WritingMode::Horizontal,
+                underline: Decoration::new(None, 0, 0),
+                strikethrough: Decoration::new(None, 0, 0),
+                blink_mode: BlinkMode::Hide,
+                blink_interval: 1,
+                text_effect: TextEffect::None,
             },
         }
     }
@@ -82,6 +100,22 @@ impl<A, V, H> TextBoxStyleBuilder<A, V, H> {
                 vertical_alignment: self.style.vertical_alignment,
                 height_mode: self.style.height_mode,
                 tab_size: self.style.tab_size,
+                tab_stops: self.style.tab_stops,
+                paragraph_spacing: self.style.paragraph_spacing,
+                first_line_indent: self.style.first_line_indent,
+                hanging_indent: self.style.hanging_indent,
+                padding: self.style.padding,
+                background_color: self.style.background_color,
+                border_color: self.style.border_color,
+                border_width: self.style.border_width,
+                corner_radius: self.style.corner_radius,
+                rotation: self.style.rotation,
+                writing_mode: self.style.writing_mode,
+                underline: self.style.underline,
+                strikethrough: self.style.strikethrough,
+                blink_mode: self.style.blink_mode,
+                blink_interval: self.style.blink_interval,
+                text_effect: self.style.text_effect,
             },
         }
     }
@@ -100,6 +134,22 @@ impl<A, V, H> TextBoxStyleBuilder<A, V, H> {
                 vertical_alignment,
                 height_mode: self.style.height_mode,
                 tab_size: self.style.tab_size,
+                tab_stops: self.style.tab_stops,
+                paragraph_spacing: self.style.paragraph_spacing,
+                first_line_indent: self.style.first_line_indent,
+                hanging_indent: self.style.hanging_indent,
+                padding: self.style.padding,
+                background_color: self.style.background_color,
+                border_color: self.style.border_color,
+                border_width: self.style.border_width,
+                corner_radius: self.style.corner_radius,
+                rotation: self.style.rotation,
+                writing_mode: self.style.writing_mode,
+                underline: self.style.underline,
+                strikethrough: self.style.strikethrough,
+                blink_mode: self.style.blink_mode,
+                blink_interval: self.style.blink_interval,
+                text_effect: self.style.text_effect,
             },
         }
     }
@@ -115,6 +165,22 @@ impl<A, V, H> TextBoxStyleBuilder<A, V, H> {
                 vertical_alignment: self.style.vertical_alignment,
                 height_mode,
                 tab_size: self.style.tab_size,
+                tab_stops: self.style.tab_stops,
+                paragraph_spacing: self.style.paragraph_spacing,
+                first_line_indent: self.style.first_line_indent,
+                hanging_indent: self.style.hanging_indent,
+                padding: self.style.padding,
+                background_color: self.style.background_color,
+                border_color: self.style.border_color,
+                border_width: self.style.border_width,
+                corner_radius: self.style.corner_radius,
+                rotation: self.style.rotation,
+                writing_mode: self.style.writing_mode,
+                underline: self.style.underline,
+                strikethrough: self.style.strikethrough,
+                blink_mode: self.style.blink_mode,
+                blink_interval: self.style.blink_interval,
+                text_effect: self.style.text_effect,
             },
         }
     }
@@ -127,6 +193,254 @@ impl<A, V, H> TextBoxStyleBuilder<A, V, H> {
 
         self
     }
+
+    /// Sets explicit tab stop positions.
+    ///
+    /// The tab stops are sorted by their positions. Tabs that are placed after the last tab stop
+    /// use the tab size set by [`tab_size`]. Only the first [`MAX_TAB_STOPS`] tab stops are used,
+    /// and in debug builds, passing more tab stops panics.
+    ///
+    /// [`tab_size`]: #method.tab_size
+    /// [`MAX_TAB_STOPS`]: ../constant.MAX_TAB_STOPS.html
+    #[inline]
+    #[must_use]
+    pub fn tab_stops(mut self, tab_stops: &[TabStop]) -> Self {
+        self.style.tab_stops = TabStops::from_slice(tab_stops);
+
+        self
+    }
+
+    /// Sets the additional space inserted after each paragraph, in pixels.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::prelude::*;
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .paragraph_spacing(4)
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn paragraph_spacing(mut self, paragraph_spacing: u32) -> Self {
+        self.style.paragraph_spacing = paragraph_spacing;
+
+        self
+    }
+
+    /// Sets the indentation of the first line of each paragraph, in pixels.
+    #[inline]
+    #[must_use]
+    pub fn first_line_indent(mut self, indent: u32) -> Self {
+        self.style.first_line_indent = indent;
+
+        self
+    }
+
+    /// Sets the indentation of the lines following the first line of each paragraph, in pixels.
+    ///
+    /// Use this together with [`first_line_indent`] to create hanging indentation.
+    ///
+    /// [`first_line_indent`]: #method.first_line_indent
+    #[inline]
+    #[must_use]
+    pub fn hanging_indent(mut self, indent: u32) -> Self {
+        self.style.hanging_indent = indent;
+
+        self
+    }
+
+    /// Sets the space between the edges of the text box and the text.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::{prelude::*, style::Padding};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .padding(Padding::symmetric(2, 4))
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.style.padding = padding;
+
+        self
+    }
+
+    /// Sets the color used to fill the text box before drawing the text.
+    ///
+    /// The whole text box is filled, including the padding.
+    #[inline]
+    #[must_use]
+    pub fn background_color(mut self, color: Rgb) -> Self {
+        self.style.background_color = Some(color);
+
+        self
+    }
+
+    /// Sets the color of the border drawn around the text box.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::{prelude::*, style::color::Rgb};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .background_color(Rgb::new(0, 0, 128))
+    ///     .border_color(Rgb::new(255, 255, 255))
+    ///     .border_width(2)
+    ///     .corner_radius(4)
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn border_color(mut self, color: Rgb) -> Self {
+        self.style.border_color = Some(color);
+
+        self
+    }
+
+    /// Sets the width of the border, in pixels.
+    #[inline]
+    #[must_use]
+    pub fn border_width(mut self, width: u32) -> Self {
+        self.style.border_width = width;
+
+        self
+    }
+
+    /// Sets the radius of the text box corners, in pixels.
+    #[inline]
+    #[must_use]
+    pub fn corner_radius(mut self, radius: u32) -> Self {
+        self.style.corner_radius = radius;
+
+        self
+    }
+
+    /// Sets the rotation of the text box contents.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::{prelude::*, style::Rotation};
+    /// #
+    /// // A side label that reads from bottom to top.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .rotation(Rotation::Rotate270)
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.style.rotation = rotation;
+
+        self
+    }
+
+    /// Sets the writing mode of the text box.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::{prelude::*, style::WritingMode};
+    /// #
+    /// // Characters are stacked from top to bottom, columns progress from right to left.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .writing_mode(WritingMode::Vertical)
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn writing_mode(mut self, writing_mode: WritingMode) -> Self {
+        self.style.writing_mode = writing_mode;
+
+        self
+    }
+
+    /// Sets the options of underlines enabled by ANSI sequences.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::{prelude::*, style::{color::Rgb, Decoration}};
+    /// #
+    /// // Red underlines, moved down by a pixel and two pixels thick.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .underline(Decoration::new(Some(Rgb::new(255, 0, 0)), 1, 1))
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn underline(mut self, underline: Decoration) -> Self {
+        self.style.underline = underline;
+
+        self
+    }
+
+    /// Sets the options of strikethrough lines enabled by ANSI sequences.
+    #[inline]
+    #[must_use]
+    pub fn strikethrough(mut self, strikethrough: Decoration) -> Self {
+        self.style.strikethrough = strikethrough;
+
+        self
+    }
+
+    /// Sets how blinking text is drawn when it is switched off.
+    #[inline]
+    #[must_use]
+    pub fn blink_mode(mut self, blink_mode: BlinkMode) -> Self {
+        self.style.blink_mode = blink_mode;
+
+        self
+    }
+
+    /// Sets the number of animation ticks between the changes of rapidly blinking text.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_text::prelude::*;
+    /// #
+    /// // With a tick every 100ms, rapidly blinking text changes every 300ms.
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .blink_interval(3)
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn blink_interval(mut self, blink_interval: u32) -> Self {
+        self.style.blink_interval = blink_interval;
+
+        self
+    }
+
+    /// Sets the effect drawn behind the text.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use embedded_graphics::geometry::Point;
+    /// # use embedded_text::{prelude::*, style::{color::Rgb, TextEffect}};
+    /// #
+    /// let style = TextBoxStyleBuilder::new()
+    ///     .text_effect(TextEffect::Shadow {
+    ///         offset: Point::new(1, 1),
+    ///         color: Rgb::new(0, 0, 0),
+    ///     })
+    ///     .build();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn text_effect(mut self, text_effect: TextEffect) -> Self {
+        self.style.text_effect = text_effect;
+
+        self
+    }
 }
 
 impl<A, V, H> TextBoxStyleBuilder<A, V, H>
@@ -2,7 +2,7 @@
 use embedded_graphics::pixelcolor::{BinaryColor, Rgb555, Rgb565, Rgb888};
 
 /// 24bit RGB color
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Rgb {
     /// Red channel value
     pub r: u8,
@@ -12,7 +12,7 @@ use crate::{
     TextBox,
 };
 use core::ops::Range;
-use embedded_graphics::{geometry::Dimensions, text::renderer::TextRenderer};
+use embedded_graphics::text::renderer::TextRenderer;
 
 /// Specifies how the [`TextBox`]'s height is adjusted when it is turned into a [`StyledTextBox`].
 ///
@@ -265,7 +265,7 @@ where
         A: HorizontalTextAlignment,
         V: VerticalTextAlignment,
     {
-        text_box.fit_height_limited(text_box.bounding_box().size.height);
+        text_box.fit_height_limited(text_box.logical_bounds().size.height);
     }
 
     #[inline]
@@ -273,3 +273,66 @@ where
         OV::calculate_displayed_row_range(cursor)
     }
 }
+
+/// Sets the height of the [`TextBox`] to fit the text, and shrinks its width to the widest line.
+///
+/// The original width of the [`TextBox`] is the maximum width of the text. This mode is useful
+/// for tooltips, buttons and other boxes that should be as small as their text.
+///
+/// Note: in this mode, vertical alignment is meaningless. Make sure to use [`TopAligned`] for
+/// efficiency.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_text::prelude::*;
+/// use embedded_graphics::{
+///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+///
+/// let character_style = MonoTextStyleBuilder::new()
+///     .font(&FONT_6X9)
+///     .text_color(BinaryColor::On)
+///     .build();
+///
+/// let style = TextBoxStyleBuilder::new()
+///     .height_mode(FitToTextSize)
+///     .build();
+///
+/// // The widest line is 9 characters wide.
+/// let text_box = TextBox::with_textbox_style(
+///     "Two lines\nof text",
+///     Rectangle::new(Point::zero(), Size::new(120, 0)),
+///     character_style,
+///     style,
+/// );
+///
+/// let size = text_box.bounding_box().size;
+/// assert_eq!(size, Size::new(54, 18));
+/// ```
+///
+/// [`TopAligned`]: ../../alignment/top/struct.TopAligned.html
+/// [`TextBox`]: ../../struct.TextBox.html
+#[derive(Copy, Clone, Debug)]
+pub struct FitToTextSize;
+
+impl HeightMode for FitToTextSize {
+    #[inline]
+    fn apply<F, A, V>(text_box: &mut TextBox<'_, F, A, V, Self>)
+    where
+        F: TextRenderer,
+        A: HorizontalTextAlignment,
+        V: VerticalTextAlignment,
+    {
+        text_box.fit_size();
+    }
+
+    #[inline]
+    fn calculate_displayed_row_range(cursor: &Cursor) -> Range<i32> {
+        // The bounding box is set to the exact size of the text, so every row is always fully
+        // displayed
+        0..cursor.line_height()
+    }
+}
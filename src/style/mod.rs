@@ -119,11 +119,19 @@
 //!
 //!  * `\x1b[0m`: Reset everything
 //!  * `\x1b[4m`: Underlined text
+//!  * `\x1b[21m`: Double underlined text
 //!  * `\x1b[24m`: Turn off text underline
+//!  * `\x1b[5m`, `\x1b[6m`: Slowly or rapidly blinking text
+//!  * `\x1b[25m`: Turn off blinking
 //!  * `\x1b[9m`: Crossed out/strikethrough text
 //!  * `\x1b[29m`: Turn off strikethrough
 //!  * `\x1b[39m`: Reset text color
 //!  * `\x1b[49m`: Reset background color
+//!  * `\x1b[58;5;<n>m`, `\x1b[58;2;<r>;<g>;<b>m`: Change underline color
+//!  * `\x1b[59m`: Reset underline color to the text color
+//!
+//! The color, position and thickness of the lines can be changed using the `underline` and
+//! `strikethrough` options of [`TextBoxStyle`].
 //!
 //! Reset style options to default
 //! ------------------------------
@@ -159,20 +167,27 @@ pub mod color;
 pub mod height_mode;
 pub mod vertical_overdraw;
 
-use core::convert::Infallible;
+use core::{convert::Infallible, fmt};
 
+#[cfg(feature = "ansi")]
+use crate::rendering::ansi::Sgr;
 use crate::{
     alignment::{HorizontalTextAlignment, LeftAligned, TopAligned, VerticalTextAlignment},
     parser::{Parser, Token},
+    renderer::{styled_width, Spans, StyledPieces},
     rendering::{
         cursor::LineCursor,
         line_iter::{ElementHandler, LineElementParser},
         space_config::UniformSpaceConfig,
     },
-    style::{height_mode::Exact, vertical_overdraw::FullRowsOnly},
-    utils::str_width,
+    style::{color::Rgb, height_mode::Exact, vertical_overdraw::FullRowsOnly},
+    utils::{str_vertical_metrics, str_width},
+};
+use embedded_graphics::{
+    geometry::{Point, Size},
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, LineHeight},
 };
-use embedded_graphics::text::{renderer::TextRenderer, LineHeight};
 
 pub use self::builder::TextBoxStyleBuilder;
 
@@ -207,6 +222,515 @@ impl TabSize {
     }
 }
 
+/// Alignment of the text that follows a [`TabStop`].
+///
+/// [`TabStop`]: struct.TabStop.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TabAlignment {
+    /// The text starts at the tab stop.
+    Left,
+
+    /// The text ends at the tab stop.
+    Right,
+
+    /// The text is centered on the tab stop.
+    Center,
+
+    /// The first occurrence of the given separator character is placed at the tab stop.
+    ///
+    /// If the text does not contain the separator, it is right aligned to the tab stop.
+    Decimal(char),
+}
+
+/// An explicit tab stop.
+///
+/// Tab stop positions are measured in pixels from the start of the line. Text that follows a tab
+/// character is aligned to the first tab stop that is to the right of the current position. After
+/// the last tab stop, tabs are handled according to the [`TabSize`] setting.
+///
+/// The text that is aligned to a tab stop ends at the next tab character or at the end of the line.
+///
+/// # Example
+///
+/// ```rust
+/// # use embedded_text::{prelude::*, style::TabStop};
+/// #
+/// // Left aligned labels, values right aligned to 120px
+/// let style = TextBoxStyleBuilder::new()
+///     .tab_stops(&[TabStop::right(120)])
+///     .build();
+/// ```
+///
+/// [`TabSize`]: enum.TabSize.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TabStop {
+    /// Position of the tab stop, in pixels.
+    pub position: u32,
+
+    /// Alignment of the text following the tab.
+    pub alignment: TabAlignment,
+}
+
+impl TabStop {
+    /// Creates a new tab stop.
+    #[inline]
+    #[must_use]
+    pub const fn new(position: u32, alignment: TabAlignment) -> Self {
+        Self {
+            position,
+            alignment,
+        }
+    }
+
+    /// Creates a new left aligned tab stop.
+    #[inline]
+    #[must_use]
+    pub const fn left(position: u32) -> Self {
+        Self::new(position, TabAlignment::Left)
+    }
+
+    /// Creates a new right aligned tab stop.
+    #[inline]
+    #[must_use]
+    pub const fn right(position: u32) -> Self {
+        Self::new(position, TabAlignment::Right)
+    }
+
+    /// Creates a new center aligned tab stop.
+    #[inline]
+    #[must_use]
+    pub const fn center(position: u32) -> Self {
+        Self::new(position, TabAlignment::Center)
+    }
+
+    /// Creates a new tab stop that aligns text on the decimal point (`.`).
+    #[inline]
+    #[must_use]
+    pub const fn decimal(position: u32) -> Self {
+        Self::new(position, TabAlignment::Decimal('.'))
+    }
+}
+
+/// The maximum number of explicit tab stops a [`TabStops`] list can hold.
+///
+/// [`TabStops`]: struct.TabStops.html
+pub const MAX_TAB_STOPS: usize = 8;
+
+/// A list of explicit tab stops.
+///
+/// The list holds up to [`MAX_TAB_STOPS`] tab stops. The tab stops are stored by value, so they
+/// can be created at runtime.
+///
+/// [`MAX_TAB_STOPS`]: constant.MAX_TAB_STOPS.html
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+pub struct TabStops {
+    stops: [TabStop; MAX_TAB_STOPS],
+    len: usize,
+}
+
+impl TabStops {
+    /// Creates an empty list of tab stops.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            stops: [TabStop::left(0); MAX_TAB_STOPS],
+            len: 0,
+        }
+    }
+
+    /// Creates a list of tab stops from a slice.
+    ///
+    /// The tab stops are sorted by their positions. Tab stops after the first [`MAX_TAB_STOPS`]
+    /// are ignored, and in debug builds, this function panics if the slice is longer than that.
+    ///
+    /// [`MAX_TAB_STOPS`]: constant.MAX_TAB_STOPS.html
+    #[inline]
+    #[must_use]
+    pub fn from_slice(tab_stops: &[TabStop]) -> Self {
+        debug_assert!(
+            tab_stops.len() <= MAX_TAB_STOPS,
+            "a list can hold at most {} tab stops",
+            MAX_TAB_STOPS
+        );
+
+        let mut list = Self::new();
+        for stop in tab_stops.iter().take(MAX_TAB_STOPS) {
+            let _ = list.push(*stop);
+        }
+
+        list
+    }
+
+    /// Inserts a tab stop into the list, keeping the tab stops sorted by their positions.
+    ///
+    /// Returns the tab stop as an error if the list is full.
+    #[inline]
+    pub fn push(&mut self, tab_stop: TabStop) -> Result<(), TabStop> {
+        if self.len == MAX_TAB_STOPS {
+            return Err(tab_stop);
+        }
+
+        let mut index = self.len;
+        while index > 0 && self.stops[index - 1].position > tab_stop.position {
+            self.stops[index] = self.stops[index - 1];
+            index -= 1;
+        }
+
+        self.stops[index] = tab_stop;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Returns the tab stops as a slice.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[TabStop] {
+        &self.stops[..self.len]
+    }
+}
+
+impl Default for TabStops {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for TabStops {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+/// Empty space between the edges of the [`TextBox`] and the text.
+///
+/// [`TextBox`]: ../struct.TextBox.html
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Padding {
+    /// Space above the text, in pixels.
+    pub top: u32,
+
+    /// Space right of the text, in pixels.
+    pub right: u32,
+
+    /// Space below the text, in pixels.
+    pub bottom: u32,
+
+    /// Space left of the text, in pixels.
+    pub left: u32,
+}
+
+impl Padding {
+    /// Creates a new padding object with the given values.
+    #[inline]
+    #[must_use]
+    pub const fn new(top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Creates a new padding object with the same value on every side.
+    #[inline]
+    #[must_use]
+    pub const fn all(padding: u32) -> Self {
+        Self::new(padding, padding, padding, padding)
+    }
+
+    /// Creates a new padding object with the given vertical and horizontal values.
+    #[inline]
+    #[must_use]
+    pub const fn symmetric(vertical: u32, horizontal: u32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    /// Returns the sum of the top and bottom padding.
+    #[inline]
+    #[must_use]
+    pub fn vertical(&self) -> u32 {
+        self.top.saturating_add(self.bottom)
+    }
+
+    /// Returns the sum of the left and right padding.
+    #[inline]
+    #[must_use]
+    pub fn horizontal(&self) -> u32 {
+        self.left.saturating_add(self.right)
+    }
+
+    /// Shrinks the given rectangle by the padding.
+    #[inline]
+    #[must_use]
+    pub(crate) fn apply(&self, rectangle: Rectangle) -> Rectangle {
+        Rectangle::new(
+            rectangle.top_left + Point::new(self.left as i32, self.top as i32),
+            Size::new(
+                rectangle.size.width.saturating_sub(self.horizontal()),
+                rectangle.size.height.saturating_sub(self.vertical()),
+            ),
+        )
+    }
+}
+
+/// Rotation of the text box contents, in clockwise quarter turns.
+///
+/// The text is laid out in a rotated coordinate system: for quarter turns, the width of the
+/// text box is used as the height of the text, and the height of the text box as the line width.
+/// Padding, indentation and other style options apply in the rotated coordinate system.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Rotation {
+    /// The text is not rotated.
+    None,
+
+    /// The text is rotated by 90° clockwise, lines read from top to bottom.
+    Rotate90,
+
+    /// The text is rotated by 180°, upside down.
+    Rotate180,
+
+    /// The text is rotated by 270° clockwise, lines read from bottom to top.
+    Rotate270,
+}
+
+impl Default for Rotation {
+    #[inline]
+    fn default() -> Self {
+        Rotation::None
+    }
+}
+
+impl Rotation {
+    /// Returns `true` if the rotation swaps the horizontal and vertical axes.
+    #[inline]
+    #[must_use]
+    pub fn is_quarter_turn(self) -> bool {
+        matches!(self, Rotation::Rotate90 | Rotation::Rotate270)
+    }
+
+    /// Converts a size between the display and the rotated coordinate system.
+    #[inline]
+    #[must_use]
+    pub fn rotate_size(self, size: Size) -> Size {
+        if self.is_quarter_turn() {
+            Size::new(size.height, size.width)
+        } else {
+            size
+        }
+    }
+
+    /// Maps a point of the rotated coordinate system to the display.
+    ///
+    /// The rotated coordinate system has its origin in the top left corner of the rotated
+    /// `bounds`. If the text is not rotated, the coordinate systems are the same.
+    pub(crate) fn to_display(self, point: Point, bounds: &Rectangle) -> Point {
+        let right = bounds.size.width as i32 - 1;
+        let bottom = bounds.size.height as i32 - 1;
+
+        match self {
+            Rotation::None => point,
+            Rotation::Rotate90 => bounds.top_left + Point::new(right - point.y, point.x),
+            Rotation::Rotate180 => bounds.top_left + Point::new(right - point.x, bottom - point.y),
+            Rotation::Rotate270 => bounds.top_left + Point::new(point.y, bottom - point.x),
+        }
+    }
+
+    /// Maps a point of the display to the rotated coordinate system.
+    pub(crate) fn to_text(self, point: Point, bounds: &Rectangle) -> Point {
+        let right = bounds.size.width as i32 - 1;
+        let bottom = bounds.size.height as i32 - 1;
+        let offset = point - bounds.top_left;
+
+        match self {
+            Rotation::None => point,
+            Rotation::Rotate90 => Point::new(offset.y, right - offset.x),
+            Rotation::Rotate180 => Point::new(right - offset.x, bottom - offset.y),
+            Rotation::Rotate270 => Point::new(bottom - offset.y, offset.x),
+        }
+    }
+}
+
+/// Direction in which characters and lines progress.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum WritingMode {
+    /// Characters are placed from left to right, lines from top to bottom.
+    Horizontal,
+
+    /// Characters are stacked from top to bottom, columns progress from right to left.
+    ///
+    /// Columns are as wide as the line height of the character style, and the `line_height`
+    /// option of the text box style sets the distance between columns. Text is wrapped at the
+    /// bottom of the text box, at any character. Newline characters start a new column.
+    ///
+    /// The horizontal alignment places the text inside the columns: left aligned text starts at
+    /// the top, right aligned text ends at the bottom of the columns. Justified text is top
+    /// aligned. The vertical alignment places the columns: top aligned columns start at the
+    /// right edge, bottom aligned columns end at the left edge of the text box.
+    ///
+    /// Spaces are as tall as the characters, and tabs use the `tab_size` option. The
+    /// `FitToText` and `ShrinkToText` height modes set the height to the smallest height at
+    /// which the columns fit into the width of the text box.
+    ///
+    /// Indentation, paragraph spacing and explicit tab stops don't apply to vertical text.
+    Vertical,
+}
+
+impl Default for WritingMode {
+    #[inline]
+    fn default() -> Self {
+        WritingMode::Horizontal
+    }
+}
+
+/// Options of the underline or strikethrough lines enabled by ANSI sequences.
+///
+/// By default, lines are drawn by the font, using the text color. If the offset or the thickness
+/// is changed, the text box draws the lines by repeating the line of the font. Double underlines
+/// are drawn by adding a second line above the first one, separated by a pixel row.
+///
+/// Lines are clipped to the line of text they belong to.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Decoration {
+    /// Color of the line.
+    ///
+    /// If `None`, the line is drawn using the text color. The color of underlines can also be
+    /// changed by ANSI sequences.
+    pub color: Option<Rgb>,
+
+    /// Vertical offset of the line from the position defined by the font, in pixels.
+    ///
+    /// Positive values move the line down.
+    pub offset: i32,
+
+    /// Number of pixel rows added below the line to make it thicker.
+    pub extra_thickness: u32,
+}
+
+impl Decoration {
+    /// Creates a new decoration object with the given values.
+    #[inline]
+    #[must_use]
+    pub const fn new(color: Option<Rgb>, offset: i32, extra_thickness: u32) -> Self {
+        Self {
+            color,
+            offset,
+            extra_thickness,
+        }
+    }
+}
+
+/// An effect drawn behind the text, to keep it readable on top of images.
+///
+/// Effects are drawn by repeating the text in the color of the effect before the text itself is
+/// drawn. The space needed by the effect is reserved around the text, so the effect isn't clipped
+/// at the edges of the text box. The background color of the character style covers the effect
+/// inside the text. Vertical text is drawn without effects.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TextEffect {
+    /// No effect is drawn.
+    None,
+
+    /// A copy of the text is drawn behind the text, moved by `offset`.
+    Shadow {
+        /// Position of the shadow, relative to the text.
+        offset: Point,
+
+        /// Color of the shadow.
+        color: Rgb,
+    },
+
+    /// A 1 pixel wide outline is drawn around the glyphs.
+    Outline {
+        /// Color of the outline.
+        color: Rgb,
+    },
+}
+
+impl Default for TextEffect {
+    #[inline]
+    fn default() -> Self {
+        TextEffect::None
+    }
+}
+
+/// The offsets of the copies of the text that make up an outline.
+const OUTLINE_OFFSETS: [Point; 8] = [
+    Point::new(-1, -1),
+    Point::new(0, -1),
+    Point::new(1, -1),
+    Point::new(-1, 0),
+    Point::new(1, 0),
+    Point::new(-1, 1),
+    Point::new(0, 1),
+    Point::new(1, 1),
+];
+
+impl TextEffect {
+    /// Returns the color of the effect, or `None` if no effect is drawn.
+    pub(crate) fn color(self) -> Option<Rgb> {
+        match self {
+            TextEffect::None => None,
+            TextEffect::Shadow { color, .. } | TextEffect::Outline { color } => Some(color),
+        }
+    }
+
+    /// Returns the offsets of the copies of the text that are drawn for the effect.
+    pub(crate) fn offsets(self) -> impl Iterator<Item = Point> {
+        let (shadow, outline): (_, &[Point]) = match self {
+            TextEffect::None => (None, &[]),
+            TextEffect::Shadow { offset, .. } => (Some(offset), &[]),
+            TextEffect::Outline { .. } => (None, &OUTLINE_OFFSETS),
+        };
+
+        shadow.into_iter().chain(outline.iter().copied())
+    }
+
+    /// Returns the space the effect needs around the text.
+    pub(crate) fn margins(self) -> Padding {
+        self.offsets().fold(Padding::all(0), |margins, offset| {
+            Padding::new(
+                margins.top.max(0i32.saturating_sub(offset.y).max(0) as u32),
+                margins.right.max(offset.x.max(0) as u32),
+                margins.bottom.max(offset.y.max(0) as u32),
+                margins
+                    .left
+                    .max(0i32.saturating_sub(offset.x).max(0) as u32),
+            )
+        })
+    }
+}
+
+/// How blinking text is drawn when it is switched off.
+///
+/// Text blinks if it is marked using the `\x1b[5m` (slow blink) or the `\x1b[6m` (rapid blink)
+/// sequence. Whether blinking text is on or off depends on the tick of the [`Animation`] of the
+/// text box.
+///
+/// [`Animation`]: ../animation/struct.Animation.html
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum BlinkMode {
+    /// Only the background is drawn.
+    Hide,
+
+    /// The text color and the background color are swapped.
+    ///
+    /// Only the colors that are set using ANSI sequences can be swapped. Colors of the character
+    /// style that weren't changed are swapped as transparent.
+    Reverse,
+}
+
+impl Default for BlinkMode {
+    #[inline]
+    fn default() -> Self {
+        BlinkMode::Hide
+    }
+}
+
 /// Styling options of a [`TextBox`].
 ///
 /// `TextBoxStyle` contains the font, foreground and background `PixelColor`, line spacing,
@@ -240,6 +764,68 @@ pub struct TextBoxStyle<A, V, H> {
 
     /// Desired column width for tabs
     pub tab_size: TabSize,
+
+    /// Explicit tab stop positions, in increasing order.
+    ///
+    /// Tabs that are placed after the last tab stop use `tab_size`.
+    pub tab_stops: TabStops,
+
+    /// Additional vertical space inserted after each paragraph, in pixels.
+    ///
+    /// A paragraph is a block of text that ends with a newline (`\n`) character.
+    pub paragraph_spacing: u32,
+
+    /// Indentation of the first line of each paragraph, in pixels.
+    pub first_line_indent: u32,
+
+    /// Indentation of every line of a paragraph, except the first one, in pixels.
+    pub hanging_indent: u32,
+
+    /// Space between the edges of the text box and the text.
+    pub padding: Padding,
+
+    /// Color used to fill the whole text box, including the padding, before drawing the text.
+    ///
+    /// If `None`, the background is left untouched.
+    pub background_color: Option<Rgb>,
+
+    /// Color of the border drawn around the text box.
+    ///
+    /// If `None`, no border is drawn.
+    pub border_color: Option<Rgb>,
+
+    /// Width of the border, in pixels.
+    ///
+    /// The border is drawn inside the bounding box of the text box, and the text is placed inside
+    /// the border and the padding. The space taken up by the border is reserved even if
+    /// `border_color` is `None`.
+    pub border_width: u32,
+
+    /// Radius of the corners of the background and the border, in pixels.
+    pub corner_radius: u32,
+
+    /// Rotation of the text box contents.
+    pub rotation: Rotation,
+
+    /// Direction in which characters and lines progress.
+    pub writing_mode: WritingMode,
+
+    /// Options of underlines enabled by ANSI sequences.
+    pub underline: Decoration,
+
+    /// Options of strikethrough lines enabled by ANSI sequences.
+    pub strikethrough: Decoration,
+
+    /// How blinking text is drawn when it is switched off.
+    pub blink_mode: BlinkMode,
+
+    /// Number of animation ticks between the changes of rapidly blinking text.
+    ///
+    /// Slowly blinking text changes half as often. Blinking text is on for the first interval.
+    pub blink_interval: u32,
+
+    /// Effect drawn behind the text.
+    pub text_effect: TextEffect,
 }
 
 impl TextBoxStyle<LeftAligned, TopAligned, Exact<FullRowsOnly>> {
@@ -270,7 +856,7 @@ impl Default for TextBoxStyle<LeftAligned, TopAligned, Exact<FullRowsOnly>> {
 }
 
 /// Information about a line.
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct LineMeasurement {
     /// Maximum line width in pixels.
     pub max_line_width: u32,
@@ -280,28 +866,81 @@ pub struct LineMeasurement {
 
     /// Whether this line is the last line of a paragraph.
     pub last_line: bool,
+
+    /// Distance between the top of the line and the baseline, in pixels.
+    ///
+    /// This is the largest ascent of the text runs in the line.
+    pub ascent: u32,
+
+    /// Distance between the baseline and the bottom of the line, in pixels.
+    ///
+    /// This is the largest descent of the text runs in the line. If the line is shorter than the
+    /// line height of the character style, the remaining space is added to the descent.
+    pub descent: u32,
+}
+
+impl LineMeasurement {
+    /// Returns the height of the line, which is the sum of the ascent and the descent.
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.ascent + self.descent
+    }
 }
 
 struct MeasureLineElementHandler<'a, S> {
     style: &'a S,
+    spans: Option<&'a dyn Spans<S>>,
     right: u32,
     max_line_width: u32,
     pos: u32,
+    ascent: u32,
+    descent: u32,
+    double_underline: bool,
+    underline_space: u32,
+}
+
+impl<'a, S: TextRenderer> MeasureLineElementHandler<'a, S> {
+    /// Makes room below the text for the second line of a double underline.
+    fn reserve_underline_space(&mut self) {
+        if self.double_underline {
+            let (_, descent) = str_vertical_metrics(self.style, "");
+            self.descent = self
+                .descent
+                .max(descent.saturating_add(self.underline_space));
+        }
+    }
 }
 
 impl<'a, S: TextRenderer> ElementHandler for MeasureLineElementHandler<'a, S> {
     type Error = Infallible;
 
-    fn measure(&self, st: &str) -> u32 {
-        str_width(self.style, st)
+    fn measure(&self, st: &str, offset: usize) -> u32 {
+        styled_width(self.spans, self.style, st, offset)
     }
 
-    fn whitespace(&mut self, width: u32) -> Result<(), Self::Error> {
+    fn whitespace(&mut self, width: u32, _offset: usize) -> Result<(), Self::Error> {
+        if width > 0 {
+            self.reserve_underline_space();
+        }
         self.pos += width;
         Ok(())
     }
 
-    fn printed_characters(&mut self, _: &str, width: u32) -> Result<(), Self::Error> {
+    fn printed_characters(
+        &mut self,
+        st: &str,
+        width: u32,
+        offset: usize,
+    ) -> Result<(), Self::Error> {
+        for (piece, style) in StyledPieces::new(self.spans, self.style, st, offset) {
+            let (ascent, descent) =
+                str_vertical_metrics(style.as_ref().unwrap_or(self.style), piece);
+            self.ascent = self.ascent.max(ascent);
+            self.descent = self.descent.max(descent);
+        }
+        self.reserve_underline_space();
+
         self.right = self.right.max(self.pos + width);
         self.pos += width;
         Ok(())
@@ -314,6 +953,16 @@ impl<'a, S: TextRenderer> ElementHandler for MeasureLineElementHandler<'a, S> {
             .min(self.max_line_width as i32) as u32;
         Ok(())
     }
+
+    #[cfg(feature = "ansi")]
+    fn sgr(&mut self, sgr: Sgr) -> Result<(), Self::Error> {
+        match sgr {
+            Sgr::DoubleUnderline => self.double_underline = true,
+            Sgr::Underline | Sgr::UnderlineOff | Sgr::Reset => self.double_underline = false,
+            _ => {}
+        }
+        Ok(())
+    }
 }
 
 impl<A, V, H> TextBoxStyle<A, V, H>
@@ -327,19 +976,31 @@ where
     /// Instead of peeking ahead when processing tokens, this function advances the parser before
     /// processing a token. If a token opens a new line, it will be returned as the carried token.
     /// If the carried token is `None`, the parser has finished processing the text.
+    ///
+    /// `spans` change the character style of parts of the text.
+    ///
+    /// `double_underline` tells if a double underline is active at the start of the line, and is
+    /// updated to the state at the end of the line. Double underlined lines are made taller so
+    /// that the second line fits below the text.
     #[inline]
     #[must_use]
     pub(crate) fn measure_line<'a, S>(
         &self,
         character_style: &S,
+        spans: Option<&dyn Spans<S>>,
         parser: &mut Parser<'a>,
         carried_token: &mut Option<Token<'a>>,
+        double_underline: &mut bool,
         max_line_width: u32,
     ) -> LineMeasurement
     where
         S: TextRenderer,
     {
-        let cursor = LineCursor::new(max_line_width, self.tab_size.into_pixels(character_style));
+        let cursor = LineCursor::new(
+            max_line_width,
+            self.tab_size.into_pixels(character_style),
+            self.tab_stops,
+        );
 
         let mut iter = LineElementParser::<'_, '_, _, A>::new(
             parser,
@@ -348,23 +1009,46 @@ where
             carried_token.clone(),
         );
 
+        // Lines without printed characters still need a baseline.
+        let (ascent, descent) = str_vertical_metrics(character_style, "");
         let mut handler = MeasureLineElementHandler {
             style: character_style,
+            spans,
             right: 0,
             pos: 0,
             max_line_width,
+            ascent,
+            descent,
+            double_underline: *double_underline,
+            underline_space: self
+                .underline
+                .extra_thickness
+                .saturating_add(1)
+                .saturating_mul(2)
+                .saturating_add(self.underline.offset.max(0) as u32),
         };
         *carried_token = iter.process(&mut handler).unwrap();
+        *double_underline = handler.double_underline;
+
+        // Lines are at least as tall as the line height of the character style. The extra space
+        // is added below the baseline.
+        let descent = handler
+            .descent
+            .max(character_style.line_height().saturating_sub(handler.ascent));
 
         LineMeasurement {
             max_line_width,
             width: handler.right,
             last_line: carried_token.is_none() || *carried_token == Some(Token::NewLine),
+            ascent: handler.ascent,
+            descent,
         }
     }
 
     /// Measures text height when rendered using a given width.
     ///
+    /// *Note:* `max_width` and the returned height refer to the area inside the padding.
+    ///
     /// # Example: measure height of text when rendered using a 6x8 MonoFont and 72px width.
     ///
     /// ```rust
@@ -405,37 +1089,125 @@ where
     where
         S: TextRenderer,
     {
-        let mut n_lines = 0_u32;
+        self.measure_text_size(character_style, text, max_width)
+            .height
+    }
+
+    /// Measures the size of the text when rendered using a given maximum width.
+    ///
+    /// The returned width is the width of the widest line, including indentation. The height is
+    /// the same as the one returned by [`measure_text_height`].
+    ///
+    /// Vertical text is measured at the smallest height at which its columns fit into
+    /// `max_width`.
+    ///
+    /// *Note:* `max_width` and the returned size refer to the area inside the padding.
+    ///
+    /// # Example: measure text rendered using a 6x9 MonoFont and 72px maximum width.
+    ///
+    /// ```rust
+    /// # use embedded_text::style::builder::TextBoxStyleBuilder;
+    /// # use embedded_graphics::{
+    /// #     geometry::Size,
+    /// #     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+    /// #     pixelcolor::BinaryColor,
+    /// # };
+    /// #
+    /// let character_style = MonoTextStyleBuilder::new()
+    ///     .font(&FONT_6X9)
+    ///     .text_color(BinaryColor::On)
+    ///     .build();
+    /// let style = TextBoxStyleBuilder::new().build();
+    ///
+    /// let size = style.measure_text_size(&character_style, "Hello,\nWorld! Hi!", 72);
+    ///
+    /// // The widest line is "World! Hi!", 10 characters.
+    /// assert_eq!(Size::new(10 * 6, 2 * 9), size);
+    /// ```
+    ///
+    /// [`measure_text_height`]: #method.measure_text_height
+    #[inline]
+    #[must_use]
+    pub fn measure_text_size<S>(&self, character_style: &S, text: &str, max_width: u32) -> Size
+    where
+        S: TextRenderer,
+    {
+        self.measure_spans_size(character_style, None, text, max_width)
+    }
+
+    /// Measures the size of a text whose character style is changed by `spans`.
+    pub(crate) fn measure_spans_size<S>(
+        &self,
+        character_style: &S,
+        spans: Option<&dyn Spans<S>>,
+        text: &str,
+        max_width: u32,
+    ) -> Size
+    where
+        S: TextRenderer,
+    {
+        if self.writing_mode == WritingMode::Vertical {
+            return self.measure_vertical_text_size(character_style, text, max_width);
+        }
+
         let mut parser = Parser::parse(text);
         let mut carry = None;
+        let mut double_underline = false;
         let mut cr_width = None;
-        let mut empty_lines = 0;
-        let line_height = self.line_height.to_absolute(character_style.line_height());
-        let last_line_height = character_style.line_height();
+        let mut paragraph_start = true;
+        let mut y = 0;
+        let mut size = Size::zero();
+
+        // Lines that are separated by carriage returns are drawn over each other. These lines
+        // form a single row, which is as tall as the tallest of them.
+        let mut cr_height = 0;
 
         loop {
-            let lm = self.measure_line(character_style, &mut parser, &mut carry, max_width);
+            let indent = if paragraph_start {
+                self.first_line_indent
+            } else {
+                self.hanging_indent
+            }
+            .min(max_width);
+            let lm = self.measure_line(
+                character_style,
+                spans,
+                &mut parser,
+                &mut carry,
+                &mut double_underline,
+                max_width - indent,
+            );
+            let line_height = lm.height().max(cr_height);
+
+            if lm.width > 0 {
+                size.width = size.width.max(indent + lm.width);
+            }
 
             if matches!(carry, Some(Token::CarriageReturn)) {
                 cr_width = cr_width.map_or(Some(lm.width), |width: u32| Some(width.max(lm.width)));
+                cr_height = line_height;
             } else {
                 let line_width = match cr_width.take() {
                     Some(width) => width.max(lm.width),
                     None => lm.width,
                 };
+                cr_height = 0;
 
+                // Lines that only contain whitespace or cursor movement are only counted if they
+                // are followed by a visible line.
                 if line_width > 0 || carry == Some(Token::NewLine) {
-                    // `empty_lines` counts lines that only contain whitespace or cursor movement
-                    n_lines += empty_lines + 1;
-                    empty_lines = 0;
-                } else {
-                    empty_lines += 1;
+                    size.height = y + line_height;
+                }
+
+                y += self.line_height.to_absolute(line_height);
+                paragraph_start = carry == Some(Token::NewLine);
+                if paragraph_start {
+                    y += self.paragraph_spacing;
                 }
             }
 
             if carry.is_none() {
-                return n_lines.saturating_sub(1) * line_height
-                    + (n_lines > 0) as u32 * last_line_height;
+                return size;
             }
         }
     }
@@ -443,9 +1215,18 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::{alignment::*, parser::Parser, style::builder::TextBoxStyleBuilder};
+    use crate::{
+        alignment::*,
+        parser::Parser,
+        style::builder::TextBoxStyleBuilder,
+        utils::test::{BelowBaseline, TallCapitals},
+    };
     use embedded_graphics::{
-        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        geometry::Size,
+        mono_font::{
+            ascii::{FONT_10X20, FONT_6X9},
+            MonoTextStyleBuilder,
+        },
         pixelcolor::BinaryColor,
         text::{renderer::TextRenderer, LineHeight},
     };
@@ -557,8 +1338,10 @@ mod test {
 
         let lm = style.measure_line(
             &character_style,
+            None,
             &mut text,
             &mut None,
+            &mut false,
             6 * FONT_6X9.character_size.width,
         );
         assert_eq!(lm.width, 6 * FONT_6X9.character_size.width);
@@ -578,8 +1361,10 @@ mod test {
 
         let lm = style.measure_line(
             &character_style,
+            None,
             &mut text,
             &mut None,
+            &mut false,
             5 * FONT_6X9.character_size.width,
         );
         assert_eq!(lm.width, 3 * FONT_6X9.character_size.width);
@@ -590,8 +1375,10 @@ mod test {
 
         let lm = style.measure_line(
             &character_style,
+            None,
             &mut text,
             &mut None,
+            &mut false,
             5 * FONT_6X9.character_size.width,
         );
         assert_eq!(lm.width, 4 * FONT_6X9.character_size.width);
@@ -610,8 +1397,10 @@ mod test {
 
         let lm = style.measure_line(
             &character_style,
+            None,
             &mut text,
             &mut None,
+            &mut false,
             5 * FONT_6X9.character_size.width,
         );
         assert_eq!(lm.width, 5 * FONT_6X9.character_size.width);
@@ -673,11 +1462,209 @@ mod test {
 
         let lm = style.measure_line(
             &character_style,
+            None,
             &mut Parser::parse("soft\u{AD}hyphen"),
             &mut None,
+            &mut false,
             50,
         );
 
         assert_eq!(lm.width, 30);
     }
+
+    #[test]
+    fn height_with_paragraph_spacing() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new()
+            .line_height(LineHeight::Pixels(11))
+            .paragraph_spacing(5)
+            .build();
+
+        // Paragraph spacing is only inserted between paragraphs, not after the last one.
+        let height = style.measure_text_height(&character_style, "word\nword word\n", 36);
+        assert_eq!(height, 11 + 5 + 11 + 9);
+
+        let height = style.measure_text_height(&character_style, "word\r\nword", 36);
+        assert_eq!(height, 11 + 5 + 9);
+    }
+
+    #[test]
+    fn height_with_indentation() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let text = "word word\nword word";
+
+        let style = TextBoxStyleBuilder::new().build();
+        let height = style.measure_text_height(&character_style, text, 9 * 6);
+        assert_eq!(height, 2 * 9);
+
+        // The first line of every paragraph is wrapped.
+        let style = TextBoxStyleBuilder::new().first_line_indent(6).build();
+        let height = style.measure_text_height(&character_style, text, 9 * 6);
+        assert_eq!(height, 4 * 9);
+
+        // Only the wrapped lines are indented.
+        let style = TextBoxStyleBuilder::new().hanging_indent(30).build();
+        let height = style.measure_text_height(&character_style, "word word word word", 9 * 6);
+        assert_eq!(height, 3 * 9);
+    }
+
+    #[test]
+    fn measure_size() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let style = TextBoxStyleBuilder::new().build();
+        let size = style.measure_text_size(&character_style, "word word word", 10 * 6);
+        assert_eq!(size, Size::new(9 * 6, 2 * 9));
+
+        // Trailing whitespace is not included in the width.
+        let size = style.measure_text_size(&character_style, "word word", 6 * 6);
+        assert_eq!(size, Size::new(4 * 6, 2 * 9));
+
+        // Indentation is included in the width.
+        let style = TextBoxStyleBuilder::new().first_line_indent(12).build();
+        let size = style.measure_text_size(&character_style, "word word", 10 * 6);
+        assert_eq!(size, Size::new(2 * 6 + 4 * 6, 2 * 9));
+
+        let size = style.measure_text_size(&character_style, "", 10 * 6);
+        assert_eq!(size, Size::zero());
+    }
+
+    #[test]
+    fn measure_line_metrics() {
+        let style = TextBoxStyleBuilder::new().build();
+
+        for font in [&FONT_6X9, &FONT_10X20].iter() {
+            let character_style = MonoTextStyleBuilder::new()
+                .font(font)
+                .text_color(BinaryColor::On)
+                .build();
+
+            // Empty lines have the same metrics as lines with text.
+            for text in ["word", ""].iter() {
+                let lm = style.measure_line(
+                    &character_style,
+                    None,
+                    &mut Parser::parse(text),
+                    &mut None,
+                    &mut false,
+                    100,
+                );
+
+                assert_eq!(lm.ascent, font.baseline);
+                assert_eq!(lm.descent, font.character_size.height - font.baseline);
+                assert_eq!(lm.height(), character_style.line_height());
+            }
+        }
+    }
+
+    #[test]
+    fn measure_height_sums_line_heights() {
+        let character_style = TallCapitals(
+            MonoTextStyleBuilder::new()
+                .font(&FONT_6X9)
+                .text_color(BinaryColor::On)
+                .build(),
+        );
+
+        let style = TextBoxStyleBuilder::new().build();
+        assert_eq!(
+            style.measure_text_height(&character_style, "a\nB c\nd", 60),
+            9 + 13 + 9
+        );
+
+        // Lines drawn over each other are as tall as the tallest of them.
+        assert_eq!(
+            style.measure_text_height(&character_style, "a\rB\nc", 60),
+            13 + 9
+        );
+
+        let style = TextBoxStyleBuilder::new()
+            .line_height(LineHeight::Percent(200))
+            .build();
+        assert_eq!(
+            style.measure_text_height(&character_style, "A\nb", 60),
+            26 + 9
+        );
+    }
+
+    #[test]
+    fn padding_sums_saturate() {
+        let padding = super::Padding::new(u32::MAX, u32::MAX, 1, 1);
+
+        assert_eq!(padding.vertical(), u32::MAX);
+        assert_eq!(padding.horizontal(), u32::MAX);
+    }
+
+    #[test]
+    fn effect_margins_saturate() {
+        let effect = super::TextEffect::Shadow {
+            offset: super::Point::new(i32::MIN, i32::MIN),
+            color: super::Rgb::new(0, 0, 0),
+        };
+
+        assert_eq!(
+            effect.margins(),
+            super::Padding::new(i32::MAX as u32, 0, 0, i32::MAX as u32)
+        );
+    }
+
+    #[test]
+    fn tab_stops_can_be_created_at_runtime() {
+        use super::{TabStop, TabStops, MAX_TAB_STOPS};
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let position = 6 * 10;
+        let style = TextBoxStyleBuilder::new()
+            .tab_stops(&[TabStop::right(position)])
+            .build();
+
+        assert_eq!(style.tab_stops.as_slice(), &[TabStop::right(position)]);
+        assert_eq!(
+            style.measure_text_size(&character_style, "a\tbc", 120),
+            Size::new(60, 9)
+        );
+
+        let mut tab_stops = TabStops::new();
+        for i in 0..MAX_TAB_STOPS as u32 {
+            assert_eq!(tab_stops.push(TabStop::left(i * 10)), Ok(()));
+        }
+        assert_eq!(tab_stops.push(TabStop::left(100)), Err(TabStop::left(100)));
+        assert_eq!(tab_stops.as_slice().len(), MAX_TAB_STOPS);
+    }
+
+    #[test]
+    fn tab_stops_are_sorted() {
+        use super::{TabStop, TabStops};
+
+        let tab_stops =
+            TabStops::from_slice(&[TabStop::left(30), TabStop::right(10), TabStop::center(20)]);
+
+        assert_eq!(
+            tab_stops.as_slice(),
+            &[TabStop::right(10), TabStop::center(20), TabStop::left(30)]
+        );
+    }
+
+    #[test]
+    fn lines_are_at_least_as_tall_as_the_line_height() {
+        let style = TextBoxStyleBuilder::new().build();
+
+        // The glyphs are 5 pixels tall, the line height of the character style is 8 pixels.
+        assert_eq!(style.measure_text_height(&BelowBaseline, "_\n_", 60), 2 * 8);
+    }
 }
@@ -0,0 +1,444 @@
+//! Table layout.
+//!
+//! A [`Table`] lays out rows of tab separated cells in columns. Column widths are computed by
+//! measuring the cells, and text that does not fit its column is wrapped inside the cell.
+//!
+//! [`Table`]: struct.Table.html
+use crate::{
+    alignment::{HorizontalTextAlignment, LeftAligned, TopAligned},
+    parser::Parser,
+    style::{color::Rgb, height_mode::FitToText, Padding, TextBoxStyle, TextBoxStyleBuilder},
+    utils::str_width,
+    TextBox,
+};
+use embedded_graphics::{
+    draw_target::{DrawTarget, DrawTargetExt},
+    geometry::{Dimensions, Point, Size},
+    primitives::Rectangle,
+    text::renderer::{CharacterStyle, TextRenderer},
+    Drawable,
+};
+
+/// The maximum number of columns of a [`Table`].
+///
+/// [`Table`]: struct.Table.html
+pub const MAX_COLUMNS: usize = 16;
+
+/// A table of text.
+///
+/// Rows of the table are separated by newline (`\n`) characters, cells in a row are separated by
+/// tab (`\t`) characters.
+///
+/// Columns are as wide as their widest cell. If the columns don't fit into the bounding box,
+/// columns that are wider than an equal share of the available space are shrunk, and their
+/// contents are wrapped. Each row is as tall as its tallest cell.
+///
+/// A table has at most [`MAX_COLUMNS`] columns, cells after the last column are not drawn.
+///
+/// Every cell is rendered as a separate [`TextBox`] using the cell style of the table. ANSI
+/// sequences only apply to the cell they are in.
+///
+/// # Example
+///
+/// ```rust
+/// use embedded_graphics::{
+///     mock_display::MockDisplay,
+///     mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+///     pixelcolor::BinaryColor,
+///     prelude::*,
+/// };
+/// use embedded_text::{prelude::*, table::Table};
+///
+/// let character_style = MonoTextStyleBuilder::new()
+///     .font(&FONT_6X9)
+///     .text_color(BinaryColor::On)
+///     .build();
+///
+/// let table = Table::new(
+///     "Brightness\t80%\nContrast\t50%",
+///     Rectangle::new(Point::zero(), Size::new(64, 64)),
+///     character_style,
+/// );
+///
+/// let mut display: MockDisplay<BinaryColor> = MockDisplay::new();
+/// table.draw(&mut display).unwrap();
+/// ```
+///
+/// [`TextBox`]: ../struct.TextBox.html
+/// [`MAX_COLUMNS`]: constant.MAX_COLUMNS.html
+#[derive(Clone, Debug)]
+pub struct Table<'a, S, A> {
+    /// The text of the table.
+    pub text: &'a str,
+
+    /// The bounding box of the table.
+    pub bounds: Rectangle,
+
+    /// The character style of the table.
+    pub character_style: S,
+
+    /// The style of the cells.
+    pub cell_style: TextBoxStyle<A, TopAligned, FitToText>,
+
+    /// Horizontal space between columns, in pixels.
+    pub column_spacing: u32,
+}
+
+impl<'a, S> Table<'a, S, LeftAligned>
+where
+    S: TextRenderer + CharacterStyle,
+{
+    /// Creates a new `Table` with left aligned cells.
+    ///
+    /// The default column spacing is the width of a space character.
+    #[inline]
+    #[must_use]
+    pub fn new(text: &'a str, bounds: Rectangle, character_style: S) -> Self {
+        Table::with_cell_style(
+            text,
+            bounds,
+            character_style,
+            TextBoxStyleBuilder::new().height_mode(FitToText).build(),
+        )
+    }
+}
+
+impl<'a, S, A> Table<'a, S, A>
+where
+    S: TextRenderer + CharacterStyle,
+    A: HorizontalTextAlignment,
+{
+    /// Creates a new `Table` with the given cell style.
+    ///
+    /// The vertical alignment and height mode of the cell style are ignored.
+    /// The default column spacing is the width of a space character.
+    #[inline]
+    #[must_use]
+    pub fn with_cell_style<V, H>(
+        text: &'a str,
+        bounds: Rectangle,
+        character_style: S,
+        cell_style: TextBoxStyle<A, V, H>,
+    ) -> Self
+    where
+        V: Copy,
+        H: Copy,
+    {
+        let column_spacing = str_width(&character_style, " ");
+
+        Self {
+            text,
+            bounds,
+            character_style,
+            cell_style: TextBoxStyleBuilder::from(&cell_style)
+                .vertical_alignment(TopAligned)
+                .height_mode(FitToText)
+                .build(),
+            column_spacing,
+        }
+    }
+
+    /// Returns an iterator over the rows of the table.
+    fn rows(&self) -> impl Iterator<Item = &'a str> + Clone {
+        // Rows that are separated by CRLF line endings end with a carriage return.
+        self.text.split('\n').map(|row| row.trim_end_matches('\r'))
+    }
+
+    /// Returns the width of a cell if it's not wrapped.
+    fn natural_cell_width(&self, cell: &str) -> u32 {
+        let mut width = 0;
+        let mut parser = Parser::parse(cell);
+        let mut carry = None;
+        let mut double_underline = false;
+
+        loop {
+            let lm = self.cell_style.measure_line(
+                &self.character_style,
+                None,
+                &mut parser,
+                &mut carry,
+                &mut double_underline,
+                u32::MAX / 2,
+            );
+            width = width.max(lm.width);
+
+            if carry.is_none() {
+                break;
+            }
+        }
+
+        let style = &self.cell_style;
+        let frame = Padding::all(style.border_width);
+        width
+            .saturating_add(style.first_line_indent.max(style.hanging_indent))
+            .saturating_add(style.padding.horizontal())
+            .saturating_add(style.text_effect.margins().horizontal())
+            .saturating_add(frame.horizontal())
+    }
+
+    /// Calculates the maximum width of the columns.
+    ///
+    /// `natural_widths` contains the width of the widest cell of each column, if not wrapped.
+    /// Columns narrower than this limit keep their natural width, wider columns are shrunk.
+    fn column_width_limit(&self, natural_widths: &[u32]) -> u32 {
+        let columns = natural_widths.len() as u32;
+        if columns == 0 {
+            return 0;
+        }
+
+        let spacing = self.column_spacing.saturating_mul(columns - 1);
+        let available = self.bounds.size.width.saturating_sub(spacing);
+
+        let mut limit = available / columns;
+        loop {
+            let (fixed_count, fixed_width) = natural_widths
+                .iter()
+                .filter(|width| **width <= limit)
+                .fold((0, 0u32), |(count, sum), width| {
+                    (count + 1, sum.saturating_add(*width))
+                });
+
+            if fixed_count == columns {
+                // Every column fits.
+                return limit.max(available);
+            }
+
+            let new_limit = available.saturating_sub(fixed_width) / (columns - fixed_count);
+            if new_limit == limit {
+                return limit;
+            }
+            limit = new_limit;
+        }
+    }
+
+    /// Measures the columns.
+    ///
+    /// Returns the width of every column, and the number of columns.
+    fn measure_columns(&self) -> ([u32; MAX_COLUMNS], usize) {
+        let mut widths = [0; MAX_COLUMNS];
+        let mut columns = 0;
+        for row in self.rows() {
+            for (column, cell) in row.split('\t').take(MAX_COLUMNS).enumerate() {
+                widths[column] = widths[column].max(self.natural_cell_width(cell));
+                columns = columns.max(column + 1);
+            }
+        }
+
+        let limit = self.column_width_limit(&widths[..columns]);
+        for width in widths[..columns].iter_mut() {
+            *width = (*width).min(limit);
+        }
+
+        (widths, columns)
+    }
+
+    /// Returns the widths of the columns.
+    ///
+    /// The returned iterator yields the width of every column, in order.
+    #[inline]
+    pub fn column_widths(&self) -> impl Iterator<Item = u32> + '_ {
+        let (widths, columns) = self.measure_columns();
+
+        (0..columns).map(move |column| widths[column])
+    }
+}
+
+impl<S, A> Dimensions for Table<'_, S, A> {
+    #[inline]
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+impl<'a, F, A> Drawable for Table<'a, F, A>
+where
+    F: TextRenderer<Color = <F as CharacterStyle>::Color> + CharacterStyle,
+    <F as CharacterStyle>::Color: From<Rgb>,
+    A: HorizontalTextAlignment,
+{
+    type Color = <F as CharacterStyle>::Color;
+    type Output = ();
+
+    #[inline]
+    fn draw<D: DrawTarget<Color = Self::Color>>(&self, display: &mut D) -> Result<(), D::Error> {
+        let mut display = display.clipped(&self.bounds);
+
+        let (widths, columns) = self.measure_columns();
+
+        let bottom = self.bounds.top_left.y + self.bounds.size.height as i32;
+        let mut y = self.bounds.top_left.y;
+        for row in self.rows() {
+            if y >= bottom {
+                break;
+            }
+
+            let mut x = self.bounds.top_left.x;
+            let mut row_height = 0;
+            for (cell, width) in row.split('\t').zip(widths[..columns].iter().copied()) {
+                let text_box = TextBox::with_textbox_style(
+                    cell,
+                    Rectangle::new(Point::new(x, y), Size::new(width, 0)),
+                    self.character_style.clone(),
+                    self.cell_style,
+                );
+                text_box.draw(&mut display)?;
+
+                row_height = row_height.max(text_box.bounds.size.height);
+                x += (width + self.column_spacing) as i32;
+            }
+
+            y += row_height as i32;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        mono_font::{ascii::FONT_6X9, MonoTextStyleBuilder},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        primitives::Rectangle,
+    };
+
+    use crate::{
+        table::{Table, MAX_COLUMNS},
+        utils::test::size_for,
+    };
+
+    #[test]
+    fn columns_keep_natural_width_if_they_fit() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let table = Table::new(
+            "key\tvalue\nlonger key\tv",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 20, 2)),
+            character_style,
+        );
+
+        assert_eq!(
+            table.column_widths().collect::<Vec<_>>(),
+            vec![10 * 6, 5 * 6]
+        );
+    }
+
+    #[test]
+    fn wide_columns_are_shrunk() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        // 20 characters minus 2 characters of column spacing are available for the text.
+        let table = Table::new(
+            "a\tsome long text\tthe longest text",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 20, 2)),
+            character_style,
+        );
+
+        assert_eq!(
+            table.column_widths().collect::<Vec<_>>(),
+            vec![6, 8 * 6 + 3, 8 * 6 + 3]
+        );
+    }
+
+    #[test]
+    fn cells_after_the_last_column_are_ignored() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let table = Table::new(
+            "a\tb\tc\td\te\tf\tg\th\ti\tj\tk\tl\tm\tn\to\tp\tq\tr",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 40, 2)),
+            character_style,
+        );
+
+        assert_eq!(table.column_widths().count(), MAX_COLUMNS);
+    }
+
+    #[test]
+    fn crlf_line_endings_separate_rows() {
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+        let bounds = Rectangle::new(Point::zero(), size_for(&FONT_6X9, 5, 2));
+
+        let lf = Table::new("a\tb\nc\td", bounds, character_style);
+        let crlf = Table::new("a\tb\r\nc\td\r\n", bounds, character_style);
+
+        assert!(crlf.rows().all(|row| !row.ends_with('\r')));
+        assert_eq!(
+            crlf.column_widths().collect::<Vec<_>>(),
+            lf.column_widths().collect::<Vec<_>>()
+        );
+
+        let mut expected = MockDisplay::new();
+        lf.draw(&mut expected).unwrap();
+
+        let mut display = MockDisplay::new();
+        crlf.draw(&mut display).unwrap();
+
+        display.assert_eq(&expected);
+    }
+
+    #[test]
+    fn render_table() {
+        let mut display = MockDisplay::new();
+
+        let character_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .background_color(BinaryColor::Off)
+            .build();
+
+        // The first column is wrapped because the table is only 3 characters wide.
+        let mut table = Table::new(
+            "a\tb\nc c\td",
+            Rectangle::new(Point::zero(), size_for(&FONT_6X9, 3, 3)),
+            character_style,
+        );
+        table.column_spacing = 0;
+
+        table.draw(&mut display).unwrap();
+
+        display.assert_pattern(&[
+            "......      ......",
+            "......      .#....",
+            "......      .#....",
+            "..###.      .###..",
+            ".#..#.      .#..#.",
+            ".#..#.      .#..#.",
+            "..###.      .###..",
+            "......      ......",
+            "......      ......",
+            "......      ......",
+            "......      ....#.",
+            "......      ....#.",
+            "..###.      ..###.",
+            ".#....      .#..#.",
+            ".#....      .#..#.",
+            "..###.      ..###.",
+            "......      ......",
+            "......      ......",
+            "......            ",
+            "......            ",
+            "......            ",
+            "..###.            ",
+            ".#....            ",
+            ".#....            ",
+            "..###.            ",
+            "......            ",
+            "......            ",
+        ]);
+    }
+}
@@ -20,20 +20,113 @@ pub fn str_width(renderer: &impl TextRenderer, s: &str) -> u32 {
     width(s) - nbsp_count * (width("\u{a0}").saturating_sub(width(" ")))
 }
 
+/// Measure the ascent and the descent of a piece of string.
+///
+/// The ascent is the distance between the top of the string and the alphabetic baseline, the
+/// descent is the distance between the baseline and the bottom of the string.
+pub fn str_vertical_metrics(renderer: &impl TextRenderer, s: &str) -> (u32, u32) {
+    let bounding_box = renderer
+        .measure_string(s, Point::zero(), Baseline::Alphabetic)
+        .bounding_box;
+
+    let ascent = (-bounding_box.top_left.y).max(0) as u32;
+    let descent = bounding_box
+        .top_left
+        .y
+        .saturating_add(bounding_box.size.height.min(i32::MAX as u32) as i32)
+        .max(0) as u32;
+
+    (ascent, descent)
+}
+
 #[cfg(test)]
 pub mod test {
     use embedded_graphics::{
+        draw_target::DrawTarget,
         mono_font::{ascii::FONT_6X9, MonoFont, MonoTextStyleBuilder},
         pixelcolor::BinaryColor,
-        prelude::Size,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+        text::{
+            renderer::{CharacterStyle, TextMetrics, TextRenderer},
+            Baseline, DecorationColor,
+        },
     };
 
-    use super::str_width;
+    use super::{str_vertical_metrics, str_width};
 
     pub fn size_for(font: &MonoFont, chars: u32, lines: u32) -> Size {
         font.character_size.x_axis() * chars + font.character_size.y_axis() * lines
     }
 
+    /// A character style that draws upper case letters taller than the font.
+    #[derive(Clone, Debug)]
+    pub struct TallCapitals<S>(pub S);
+
+    impl<S: CharacterStyle> CharacterStyle for TallCapitals<S> {
+        type Color = S::Color;
+
+        fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+            self.0.set_text_color(text_color);
+        }
+
+        fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+            self.0.set_background_color(background_color);
+        }
+
+        fn set_underline_color(&mut self, underline_color: DecorationColor<Self::Color>) {
+            self.0.set_underline_color(underline_color);
+        }
+
+        fn set_strikethrough_color(&mut self, strikethrough_color: DecorationColor<Self::Color>) {
+            self.0.set_strikethrough_color(strikethrough_color);
+        }
+    }
+
+    impl<S: TextRenderer> TextRenderer for TallCapitals<S> {
+        type Color = S::Color;
+
+        fn draw_string<D>(
+            &self,
+            text: &str,
+            position: Point,
+            baseline: Baseline,
+            target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            self.0.draw_string(text, position, baseline, target)
+        }
+
+        fn draw_whitespace<D>(
+            &self,
+            width: u32,
+            position: Point,
+            baseline: Baseline,
+            target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            self.0.draw_whitespace(width, position, baseline, target)
+        }
+
+        fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+            let mut metrics = self.0.measure_string(text, position, baseline);
+            if text.chars().any(char::is_uppercase) {
+                metrics.bounding_box.top_left.y -= 4;
+                metrics.bounding_box.size.height += 4;
+            }
+
+            metrics
+        }
+
+        fn line_height(&self) -> u32 {
+            self.0.line_height()
+        }
+    }
+
     #[test]
     fn width_of_nbsp_is_single_space() {
         let renderer = MonoTextStyleBuilder::new()
@@ -42,4 +135,64 @@ pub mod test {
             .build();
         assert_eq!(str_width(&renderer, " "), str_width(&renderer, "\u{a0}"));
     }
+
+    /// A text renderer whose glyphs are entirely below the baseline.
+    ///
+    /// The glyphs are shorter than the line height.
+    pub struct BelowBaseline;
+
+    impl TextRenderer for BelowBaseline {
+        type Color = BinaryColor;
+
+        fn draw_string<D>(
+            &self,
+            text: &str,
+            position: Point,
+            baseline: Baseline,
+            _target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            Ok(self.measure_string(text, position, baseline).next_position)
+        }
+
+        fn draw_whitespace<D>(
+            &self,
+            width: u32,
+            position: Point,
+            _baseline: Baseline,
+            _target: &mut D,
+        ) -> Result<Point, D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+        {
+            Ok(position + Point::new(width as i32, 0))
+        }
+
+        fn measure_string(&self, text: &str, position: Point, _baseline: Baseline) -> TextMetrics {
+            let width = 6 * text.chars().count() as u32;
+
+            TextMetrics {
+                bounding_box: Rectangle::new(position + Point::new(0, 2), Size::new(width, 3)),
+                next_position: position + Point::new(width as i32, 0),
+            }
+        }
+
+        fn line_height(&self) -> u32 {
+            8
+        }
+    }
+
+    #[test]
+    fn vertical_metrics() {
+        let renderer = MonoTextStyleBuilder::new()
+            .font(&FONT_6X9)
+            .text_color(BinaryColor::On)
+            .build();
+        assert_eq!(str_vertical_metrics(&renderer, "a"), (6, 3));
+
+        // The glyph starts 2 pixels below the baseline and is 3 pixels tall.
+        assert_eq!(str_vertical_metrics(&BelowBaseline, "_"), (0, 5));
+    }
 }